@@ -1,6 +1,6 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
-use chrono::{Utc, NaiveDateTime, NaiveDate};
+use chrono::{Datelike, Local, NaiveDate};
 use clap::{Parser, Subcommand};
 use aplan::prelude::*;
 
@@ -28,6 +28,59 @@ enum Commands {
         #[clap(subcommand)]
         command: MemberCommands
     },
+    /// List milestones and whether they are on track
+    Milestones { },
+    /// List late tasks and tasks due soon, with their assignees
+    Overdue {
+        /// Tasks due in up to this many days are listed as due soon
+        #[clap(short, long, value_parser, default_value = "7")]
+        days: i64
+    },
+    /// Estimate the finish date with a Monte Carlo simulation over the dependency graph
+    Simulate {
+        /// Date the remaining work starts (today if not given)
+        #[clap(short, long, value_parser = date_parser)]
+        start: Option<NaiveDate>,
+        /// Number of iterations
+        #[clap(short, long, value_parser, default_value = "10000")]
+        iterations: usize,
+        /// Seed of the random number generator
+        #[clap(long, value_parser, default_value = "0")]
+        seed: u64,
+        /// Number of most critical tasks to show
+        #[clap(short, long, value_parser, default_value = "10")]
+        top: usize
+    },
+    /// List tasks that can be worked on now
+    Next {
+        /// Show only tasks assigned to this member
+        name: Option<String>,
+        /// Order of the tasks
+        #[clap(short, long, value_enum, default_value = "priority")]
+        by: NextOrder
+    },
+    /// Plan the start and finish of every leaf that isn't done, leveling the load of the members
+    Schedule {
+        /// Date the remaining work starts (today if not given)
+        #[clap(short, long, value_parser = date_parser)]
+        start: Option<NaiveDate>
+    },
+    /// Manage the holidays of the project calendar
+    Holiday {
+        #[clap(subcommand)]
+        command: HolidayCommands
+    },
+    /// Manage teams of members and the tasks they own
+    Team {
+        #[clap(subcommand)]
+        command: TeamCommands
+    },
+    /// Check that the project file is consistent
+    Check {
+        /// Repair the problems that can be fixed safely
+        #[clap(long)]
+        fix: bool
+    },
     /// Create project file
     Init {
         /// Name of the project to create
@@ -36,10 +89,85 @@ enum Commands {
     }
 }
 
+#[derive(Subcommand)]
+enum HolidayCommands {
+    /// Add a day nobody works on
+    Add {
+        #[clap(value_parser = date_parser)]
+        date: NaiveDate
+    },
+    /// Remove a holiday
+    Remove {
+        #[clap(value_parser = date_parser)]
+        date: NaiveDate
+    },
+    /// List holidays
+    List { }
+}
+
+#[derive(Subcommand)]
+enum TeamCommands {
+    /// List teams and their members
+    List { },
+    /// Add a team
+    Add {
+        #[clap(value_parser)]
+        name: String
+    },
+    /// Remove a team, leaving the tasks assigned to it without a team
+    Remove {
+        #[clap(value_parser)]
+        name: String
+    },
+    /// Add a member to a team
+    Join {
+        #[clap(value_parser)]
+        team: String,
+        #[clap(value_parser)]
+        member: String
+    },
+    /// Remove a member from a team
+    Leave {
+        #[clap(value_parser)]
+        team: String,
+        #[clap(value_parser)]
+        member: String
+    },
+    /// Assign a task to a team as a whole
+    Assign {
+        #[clap(value_parser)]
+        team: String,
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId
+    },
+    /// Remove the team assignment of a task
+    Unassign {
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId
+    },
+    /// Show the hours each member of a team can work, and the team total
+    Capacity {
+        #[clap(value_parser)]
+        team: String,
+        /// First day (today if not given)
+        #[clap(long, value_parser = date_parser)]
+        from: Option<NaiveDate>,
+        /// Last day (two weeks after the first one if not given)
+        #[clap(long, value_parser = date_parser)]
+        to: Option<NaiveDate>,
+    },
+    /// Show the planned value, actual cost and earned value of the tasks a team owns
+    Metrics {
+        #[clap(value_parser)]
+        team: String
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 enum ShowFormat {
     Dot,
-    Text
+    Text,
+    Gantt
 }
 
 impl FromStr for ShowFormat {
@@ -49,11 +177,29 @@ impl FromStr for ShowFormat {
         match s.to_lowercase().as_str() {
             "dot" => Ok(ShowFormat::Dot),
             "text" | "txt" | "tree" => Ok(ShowFormat::Text),
+            "gantt" | "mermaid" => Ok(ShowFormat::Gantt),
             _ => Err(Error::ParseCliArgument(s.to_string()))
         }
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+enum NextOrder {
+    Priority,
+    Due,
+    Slack
+}
+
+impl From<NextOrder> for ReadyOrder {
+    fn from(order: NextOrder) -> Self {
+        match order {
+            NextOrder::Priority => ReadyOrder::Priority,
+            NextOrder::Due => ReadyOrder::DueDate,
+            NextOrder::Slack => ReadyOrder::Slack,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum MemberCommands {
     /// List members in the project
@@ -64,6 +210,15 @@ enum MemberCommands {
         #[clap(value_parser)]
         name: String
     },
+    /// Rename a member, keeping their assignments and logged hours
+    Rename {
+        /// Current name of the member
+        #[clap(value_parser)]
+        old: String,
+        /// New name of the member
+        #[clap(value_parser)]
+        new: String
+    },
     /// Remove member from project
     Remove {
         /// Name of the member to remove
@@ -77,7 +232,98 @@ enum MemberCommands {
         name: String,
         /// Task id
         #[clap(value_parser = task_id_parser)]
-        id: TaskId
+        id: TaskId,
+        /// Warn if the member lacks a skill the task requires
+        #[clap(short, long)]
+        check_skills: bool,
+        /// Percentage of the task the member does, up to 100% for all assignees. Assigning again
+        /// changes it
+        #[clap(short, long, value_parser, default_value = "100")]
+        allocation: u32
+    },
+    /// Change the hourly rate of a member from a date on
+    Rate {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Cost of an hour of work
+        #[clap(value_parser)]
+        hourly: f64,
+        /// First day of the new rate (today if not given)
+        #[clap(short, long, value_parser = date_parser)]
+        since: Option<NaiveDate>
+    },
+    /// List the hourly rates of a member
+    Rates {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String
+    },
+    /// Cancel the rate change of a member on a date
+    RemoveRate {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// First day of the rate to remove
+        #[clap(value_parser = date_parser)]
+        since: NaiveDate
+    },
+    /// Show the load of every member per week or per sprint, flagging anyone over 100%
+    Load {
+        /// First week to show (this week if not given)
+        #[clap(short, long, value_parser = date_parser)]
+        from: Option<NaiveDate>,
+        /// Number of weeks to show
+        #[clap(short, long, value_parser = clap::value_parser!(u32).range(1..), default_value = "8")]
+        weeks: u32,
+        /// Show sprints instead of weeks
+        #[clap(short, long, conflicts_with_all = &["from", "weeks"])]
+        sprints: bool,
+        /// Write comma separated values instead of a heat-map
+        #[clap(long)]
+        csv: bool,
+        /// File to write to
+        #[clap(short, long, value_parser)]
+        output: Option<String>
+    },
+    /// List the roles and skills of a member
+    Skills {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String
+    },
+    /// Add a skill to a member, or change its level
+    Skill {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Name of the skill
+        #[clap(value_parser)]
+        skill: String,
+        /// Proficiency: beginner, intermediate, advanced, expert or 1 to 4
+        #[clap(value_parser = Proficiency::from_str)]
+        level: Proficiency
+    },
+    /// Remove a skill from a member
+    RemoveSkill {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Name of the skill
+        #[clap(value_parser)]
+        skill: String
+    },
+    /// Give a role to a member
+    Role {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Name of the role
+        #[clap(value_parser)]
+        role: String,
+        /// Take the role away instead
+        #[clap(short, long)]
+        remove: bool
     },
     /// Remove Member from task
     RemoveTask {
@@ -88,27 +334,109 @@ enum MemberCommands {
         #[clap(value_parser = task_id_parser)]
         id: TaskId
     },
-    /// Add routine exception
+    /// Add routine exception, for a day, a date range or a recurring day
     AddException {
         /// Name of the member
         #[clap(value_parser)]
         name: String,
-        /// Date of the exception
+        /// Date of the exception, or first day it applies
         #[clap(value_parser = date_parser)]
         date: NaiveDate,
-        /// Cost of the exception
+        /// Hours the member is available that day, instead of their usual availability
+        #[clap(value_parser)]
+        cost: f64,
+        /// Last day of a date range
+        #[clap(long, value_parser = date_parser, conflicts_with_all = &["every", "monthly"])]
+        to: Option<NaiveDate>,
+        /// Repeat every this many weeks, on the weekday of the date
+        #[clap(long, value_parser, conflicts_with = "monthly")]
+        every: Option<u32>,
+        /// Repeat each month on the first, second, third, fourth, fifth or last weekday of the
+        /// date
+        #[clap(long, value_parser = nth_parser, allow_hyphen_values = true)]
+        monthly: Option<i8>,
+        /// Last day a recurring exception applies
+        #[clap(long, value_parser = date_parser)]
+        until: Option<NaiveDate>,
+        /// Why the usual availability doesn't apply
+        #[clap(long, value_parser)]
+        reason: Option<String>,
+    },
+    /// List the routine exceptions of a member
+    Exceptions {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+    },
+    /// Change a routine exception, by its number in the list
+    EditException {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Number of the exception
         #[clap(value_parser)]
-        cost: f64
+        index: usize,
+        /// Hours the member is available
+        #[clap(long, value_parser)]
+        hours: Option<f64>,
+        /// Last day the exception applies
+        #[clap(long, value_parser = date_parser)]
+        end: Option<NaiveDate>,
+        /// Why the usual availability doesn't apply
+        #[clap(long, value_parser)]
+        reason: Option<String>,
     },
-    /// Remove routine exception
+    /// Remove routine exceptions on a day or a date range, or a whole exception by its number
     RemoveException {
         /// Name of the member
         #[clap(value_parser)]
         name: String,
-        /// Date of the exception
-        #[clap(value_parser = date_parser)]
-        date: NaiveDate,
-    }
+        /// Date of the exception, or first day of the range
+        #[clap(value_parser = date_parser, required_unless_present = "index")]
+        date: Option<NaiveDate>,
+        /// Last day of the range
+        #[clap(long, value_parser = date_parser)]
+        to: Option<NaiveDate>,
+        /// Number of the exception to remove
+        #[clap(long, value_parser, conflicts_with_all = &["date", "to"])]
+        index: Option<usize>,
+    },
+    /// Show or change the hours a member works on each day of the week
+    Availability {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Days to change, like 'mon-thu=8,fri=4'
+        #[clap(value_parser)]
+        spec: Option<String>,
+    },
+    /// Show the hours each member can work on each day, marking routine exceptions with '*'
+    Calendar {
+        /// First day to show (today if not given)
+        #[clap(long, value_parser = date_parser)]
+        from: Option<NaiveDate>,
+        /// Last day to show (two weeks after the first one if not given)
+        #[clap(long, value_parser = date_parser)]
+        to: Option<NaiveDate>,
+    },
+    /// Show the tasks, logged effort, cost performance, upcoming exceptions and sprint
+    /// commitments of a member
+    Show {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Print the dashboard as JSON
+        #[clap(long)]
+        json: bool
+    },
+    /// List the unfinished tasks of a member and what each one is waiting on
+    Blocked {
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+    },
+    /// Show how many tasks of each member (rows) wait on tasks of each other member (columns)
+    Waiting { }
 }
 
 #[derive(Subcommand)]
@@ -116,13 +444,44 @@ enum TaskCommands {
 
     /// Visualize the Task
     Show {
-        /// Get tree in visualization string
+        /// Get tree in visualization string. Gantt output is a Mermaid chart of the dates
+        /// planned by `schedule`
         #[clap(short, long, default_value = "text")]
         format: ShowFormat,
 
         /// File to write the visualization to
         #[clap(short, long, value_parser)]
         output: Option<String>,
+
+        /// Show effort estimates and flag leaves without one
+        #[clap(short, long)]
+        estimates: bool,
+
+        /// Highlight the critical path
+        #[clap(short, long)]
+        critical_path: bool,
+
+        /// Show the dates planned by `schedule`
+        #[clap(short, long)]
+        schedule: bool,
+
+        /// Fill tasks with the color of their owning team (dot only)
+        #[clap(short, long)]
+        teams: bool,
+    },
+    /// Show earliest and latest start and finish of every leaf, in days from now
+    CriticalPath { },
+    /// Show what moves if a task finishes some days later than planned
+    Impact {
+        /// Id of the late task
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Slip in days
+        #[clap(value_parser)]
+        days: f64,
+        /// Date the remaining work starts (today if not given)
+        #[clap(short, long, value_parser = date_parser)]
+        start: Option<NaiveDate>,
     },
     /// Add new task to Task
     Add {
@@ -131,7 +490,19 @@ enum TaskCommands {
         parent: Option<TaskId>,
         /// Name of the task
         #[clap(value_parser)]
-        name: String
+        name: String,
+        /// Create a milestone with this target date instead
+        #[clap(short, long, value_parser = date_parser)]
+        milestone: Option<NaiveDate>
+    },
+    /// Set the target date of a milestone
+    TargetDate {
+        /// Milestone id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Date the milestone should be reached
+        #[clap(value_parser = date_parser)]
+        date: NaiveDate
     },
     /// Remove task from Task
     Remove {
@@ -139,14 +510,98 @@ enum TaskCommands {
         #[clap(value_parser = task_id_parser)]
         id: TaskId,
     },
+    /// Split a leaf task into subtasks, keeping its planned value and actual cost
+    Split {
+        /// Id of the task to split
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Names of the subtasks to create
+        #[clap(value_parser, required = true)]
+        names: Vec<String>,
+        /// Planned value of each subtask (split evenly if not given)
+        #[clap(long, value_parser, use_value_delimiter = true)]
+        planned_values: Option<Vec<f64>>,
+        /// Actual cost of each subtask (split evenly if not given)
+        #[clap(long, value_parser, use_value_delimiter = true)]
+        actual_costs: Option<Vec<f64>>,
+        /// Number of the subtask that receives the dependencies and members
+        #[clap(short, long, value_parser, default_value = "1")]
+        target: u32
+    },
     /// Mark a task as done
     Done {
         /// Task id
         #[clap(value_parser = task_id_parser)]
         id: TaskId,
-        /// Cost it took to complete task
+        /// Cost it took to complete task, the cost of the logged hours if not given
         #[clap(value_parser)]
-        cost: f64
+        cost: Option<f64>
+    },
+    /// Log hours a member worked on a task
+    Log {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Name of the member
+        #[clap(value_parser)]
+        name: String,
+        /// Hours worked
+        #[clap(value_parser)]
+        hours: f64,
+        /// Day the hours were worked (today if not given)
+        #[clap(short, long, value_parser = date_parser)]
+        date: Option<NaiveDate>
+    },
+    /// List the hours logged on a task
+    Logs {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId
+    },
+    /// Derive planned values and actual costs from hours and rates, and list them
+    Costs {
+        /// Date whose rates price the estimates of unplanned tasks (today if not given)
+        #[clap(short, long, value_parser = date_parser)]
+        as_of: Option<NaiveDate>
+    },
+    /// Derive the planned value and actual cost of a task again, dropping the ones set by hand
+    ResetCosts {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId
+    },
+    /// Set or clear the due date of a task
+    DueDate {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Due date of the task (cleared if not given)
+        #[clap(value_parser = date_parser)]
+        date: Option<NaiveDate>
+    },
+    /// Set or clear the effort estimate of a task, in hours
+    Estimate {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Most likely effort (cleared if not given)
+        #[clap(value_parser)]
+        hours: Option<f64>,
+        /// Optimistic effort, for three-point estimates
+        #[clap(short, long, value_parser, requires_all = &["hours", "pessimistic"])]
+        optimistic: Option<f64>,
+        /// Pessimistic effort, for three-point estimates
+        #[clap(short, long, value_parser, requires_all = &["hours", "optimistic"])]
+        pessimistic: Option<f64>
+    },
+    /// Set the priority of a task (higher is worked on first)
+    Priority {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Priority of the task
+        #[clap(value_parser, allow_hyphen_values = true)]
+        priority: i32
     },
     /// Set value of a task
     PlannedValue {
@@ -166,7 +621,10 @@ enum TaskCommands {
     /// Get list of task todo
     Todo {
         /// Show only this member's incomplete tasks
-        name: Option<String>
+        name: Option<String>,
+        /// Show only the incomplete tasks of this team and its members
+        #[clap(short, long)]
+        team: Option<String>
     },
     /// Add task dependency
     AddDependency {
@@ -174,6 +632,13 @@ enum TaskCommands {
         id: TaskId,
         #[clap(value_parser = task_id_parser)]
         dependency: TaskId,
+        /// Dependency type: fs (finish-to-start), ss, ff or sf. Adding an existing dependency
+        /// again changes its type and lag
+        #[clap(short, long, value_parser = DependencyType::from_str, default_value = "fs")]
+        kind: DependencyType,
+        /// Lag in days, negative to let the tasks overlap
+        #[clap(short, long, value_parser, default_value = "0", allow_hyphen_values = true)]
+        lag: i64,
     },
     /// Remove task dependency
     RemoveDependency {
@@ -181,31 +646,99 @@ enum TaskCommands {
         id: TaskId,
         #[clap(value_parser = task_id_parser)]
         dependency: TaskId
+    },
+    /// Remove the dependencies already implied by other ones
+    ReduceDependencies {
+        /// Only list the dependencies that would be removed
+        #[clap(short = 'n', long)]
+        dry_run: bool
+    },
+    /// Require a skill from the assignees of a task
+    RequireSkill {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Name of the skill
+        #[clap(value_parser)]
+        skill: String,
+        /// Least proficiency needed: beginner, intermediate, advanced, expert or 1 to 4
+        #[clap(value_parser = Proficiency::from_str, default_value = "beginner")]
+        level: Proficiency
+    },
+    /// Stop requiring a skill for a task
+    RemoveSkill {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId,
+        /// Name of the skill
+        #[clap(value_parser)]
+        skill: String
+    },
+    /// Rank the members that could take a task by skill match and current load
+    Suggest {
+        /// Task id
+        #[clap(value_parser = task_id_parser)]
+        id: TaskId
     }
 }
 
 fn task_id_parser(s: &str) -> Result<TaskId, String> {
-    TaskId::parse(s).or_else(|_| Err(s.to_string()))
+    TaskId::parse(s).map_err(|_| s.to_string())
+}
+
+fn nth_parser(s: &str) -> Result<i8, String> {
+    match s {
+        "first" | "1" => Ok(1),
+        "second" | "2" => Ok(2),
+        "third" | "3" => Ok(3),
+        "fourth" | "4" => Ok(4),
+        "fifth" | "5" => Ok(5),
+        "last" | "-1" => Ok(-1),
+        _ => Err(s.to_string())
+    }
 }
 
 fn date_parser(s: &str) -> Result<NaiveDate, String> {
-    NaiveDateTime::parse_from_str(s, "%d/%m/%Y")
-        .map(|dt| dt.date())
-        .map_err(|e| s.to_string())
+    NaiveDate::parse_from_str(s, "%d/%m/%Y")
+        .map_err(|_| s.to_string())
+}
+
+fn today() -> NaiveDate {
+    Local::today().naive_local()
+}
+
+fn split_amounts(amounts: &Option<Vec<f64>>) -> SplitAmounts {
+    amounts
+        .clone()
+        .map(SplitAmounts::Explicit)
+        .unwrap_or(SplitAmounts::Even)
 }
 
 fn process_tasks(command: &TaskCommands, project_filename: &str) -> Result<Project, Error> {
     let mut project = Project::load(project_filename)?;
     match command {
-        TaskCommands::Show { format, output } => {
+        TaskCommands::Show { format, output, estimates, critical_path, schedule, teams } => {
+            let teams = if *teams { project.task_teams() } else { HashMap::new() };
+            let options = ViewOptions { estimates: *estimates, critical_path: *critical_path, schedule: *schedule, teams };
             match format {
-                ShowFormat::Dot => util::to_file(output.as_deref(), project.tasks().to_dot_str())?,
-                ShowFormat::Text => util::to_file(output.as_deref(), project.tasks().to_tree_str())?,
+                ShowFormat::Dot => util::to_file(output.as_deref(), project.tasks().to_dot_str_with(&options))?,
+                ShowFormat::Text => util::to_file(output.as_deref(), project.tasks().to_tree_str_with(&options))?,
+                ShowFormat::Gantt => util::to_file(output.as_deref(), project.tasks().to_gantt_str())?,
             }
         },
-        TaskCommands::Add { parent, name } => {
+        TaskCommands::Add { parent, name, milestone } => {
+            project.tasks_mut(|tasks| {
+                let parent = parent.clone().unwrap_or(TaskId::get_root_id());
+                match milestone {
+                    Some(date) => tasks.add_milestone(parent, name, *date)?,
+                    None => tasks.add(parent, name)?
+                };
+                Ok(())
+            })?;
+        },
+        TaskCommands::TargetDate { id, date } => {
             project.tasks_mut(|tasks| {
-                tasks.add(parent.clone().unwrap_or(TaskId::get_root_id()), name)?;
+                tasks.target_date(id, *date)?;
                 Ok(())
             })?;
         },
@@ -215,9 +748,79 @@ fn process_tasks(command: &TaskCommands, project_filename: &str) -> Result<Proje
                 Ok(())
             })?;
         },
+        TaskCommands::Split { id, names, planned_values, actual_costs, target } => {
+            let names = names.iter().map(|name| name.as_str()).collect::<Vec<_>>();
+            let planned_values = split_amounts(planned_values);
+            let actual_costs = split_amounts(actual_costs);
+            project.tasks_mut(|tasks| {
+                tasks.split(id, &names, &planned_values, &actual_costs, *target)?;
+                Ok(())
+            })?;
+        },
         TaskCommands::Done { id, cost } => {
             project.tasks_mut(|tasks| {
-                tasks.done(&id, *cost)?;
+                match cost {
+                    Some(cost) => tasks.done(id, *cost)?,
+                    None => tasks.done_from_logs(id)?,
+                };
+                Ok(())
+            })?;
+        },
+        TaskCommands::Log { id, name, hours, date } => {
+            project.tasks_mut(|tasks| {
+                tasks.log_work(id, name, date.unwrap_or_else(today), *hours)?;
+                Ok(())
+            })?;
+        },
+        TaskCommands::Logs { id } => {
+            let task = project.tasks().get(id)?;
+            task.work_logs().iter().for_each(|log| println!("{}", log));
+            println!("Total: {}h", task.logged_hours());
+        },
+        TaskCommands::Costs { as_of } => {
+            project.tasks_mut(|tasks| {
+                tasks.derive_costs(as_of.unwrap_or_else(today))?;
+                Ok(())
+            })?;
+            println!("{:>10} {:>10}  task", "PV", "AC");
+            let mut leaves = project.tasks().get_tasks().filter(|t| !t.is_milestone()).collect::<Vec<_>>();
+            leaves.sort_by(|a, b| a.id().as_vec().cmp(b.id().as_vec()));
+            leaves.iter().for_each(|t| {
+                let source = |manual: bool| if manual { "*" } else { " " };
+                println!("{:>9.2}{} {:>9.2}{}  {}",
+                    t.get_planned_value(), source(t.has_manual_planned_value()),
+                    t.get_actual_cost(), source(t.has_manual_actual_cost()), t);
+            });
+            println!("{:>9.2}  {:>9.2}   total", project.tasks().planned_value(), project.tasks().actual_cost());
+            println!("* set by hand");
+        },
+        TaskCommands::ResetCosts { id } => {
+            project.tasks_mut(|tasks| {
+                tasks.reset_cost_overrides(id)?;
+                tasks.derive_costs(today())?;
+                Ok(())
+            })?;
+        },
+        TaskCommands::DueDate { id, date } => {
+            project.tasks_mut(|tasks| {
+                tasks.due_date(id, *date)?;
+                Ok(())
+            })?;
+        },
+        TaskCommands::Estimate { id, hours, optimistic, pessimistic } => {
+            let estimate = match (optimistic, hours, pessimistic) {
+                (Some(o), Some(m), Some(p)) => Some(Estimate::three_point(*o, *m, *p)?),
+                (_, Some(m), _) => Some(Estimate::new(*m)?),
+                _ => None
+            };
+            project.tasks_mut(|tasks| {
+                tasks.estimate(id, estimate)?;
+                Ok(())
+            })?;
+        },
+        TaskCommands::Priority { id, priority } => {
+            project.tasks_mut(|tasks| {
+                tasks.priority(id, *priority)?;
                 Ok(())
             })?;
         },
@@ -227,20 +830,93 @@ fn process_tasks(command: &TaskCommands, project_filename: &str) -> Result<Proje
                 Ok(())
             })?;
         },
+        TaskCommands::RequireSkill { id, skill, level } => {
+            project.tasks_mut(|tasks| {
+                tasks.require_skill(id, skill, *level)?;
+                Ok(())
+            })?;
+        },
+        TaskCommands::RemoveSkill { id, skill } => {
+            project.tasks_mut(|tasks| {
+                tasks.remove_required_skill(id, skill)?;
+                Ok(())
+            })?;
+        },
+        TaskCommands::Suggest { id } => {
+            let required = project.tasks().get(id)?.required_skills();
+            if !required.is_empty() {
+                let required = required.iter().map(|(skill, level)| format!("{} ({})", skill, level)).collect::<Vec<_>>();
+                println!("Requires: {}", required.join(", "));
+            }
+            println!("{:>6} {:>8} {:>6}  member", "match", "load", "weeks");
+            project.suggest_assignees(id)?.iter().for_each(|s| {
+                let missing = s.missing_skills.iter().map(|(skill, level)| format!("{} ({})", skill, level)).collect::<Vec<_>>();
+                let missing = if missing.is_empty() { String::new() } else { format!(" - missing {}", missing.join(", ")) };
+                println!("{:5.0}% {:7.1}h {:6.1}  {}{}", s.skill_match * 100.0, s.load, s.load_weeks, s.name, missing);
+            });
+        },
+        TaskCommands::CriticalPath {  } => {
+            let critical_path = project.tasks().critical_path();
+            println!("Remaining duration: {:.1} days", critical_path.duration);
+            println!("{:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>10} {:>10}  task", "ES", "EF", "LS", "LF", "slack", "free", "planned", "");
+            let mut leaves = project.tasks().get_tasks().collect::<Vec<_>>();
+            leaves.sort_by(|a, b| {
+                let (a_times, b_times) = (critical_path.times(a.id()).unwrap(), critical_path.times(b.id()).unwrap());
+                a_times.earliest_start.partial_cmp(&b_times.earliest_start).unwrap().then(a.id().as_vec().cmp(b.id().as_vec()))
+            });
+            leaves.iter().for_each(|t| {
+                let times = critical_path.times(t.id()).unwrap();
+                let marker = if times.is_critical() { "*" } else { " " };
+                let (planned_start, planned_finish) = t.planned_dates()
+                    .map_or(("-".to_string(), "-".to_string()), |(start, finish)| (start.to_string(), finish.to_string()));
+                println!("{:8.1} {:8.1} {:8.1} {:8.1} {:8.1} {:8.1} {:>10} {:>10} {}{}",
+                    times.earliest_start, times.earliest_finish, times.latest_start, times.latest_finish,
+                    times.total_slack(), times.free_slack, planned_start, planned_finish, marker, t);
+            });
+        },
+        TaskCommands::Impact { id, days, start } => {
+            let impact = project.slip_impact(id, *days, start.unwrap_or_else(today))?;
+            println!("Project finish: {} -> {} (+{:.1} days)", impact.old_finish, impact.new_finish, impact.project_delay);
+            println!("{:>10} {:>10} {:>6} {:>8}  task", "old", "new", "delay", "absorbed");
+            impact.tasks.iter().try_for_each(|t| {
+                println!("{:>10} {:>10} {:6.1} {:8.1}  {}", t.old_finish, t.new_finish, t.delay, t.absorbed, project.tasks().get(&t.id)?);
+                Ok::<(), Error>(())
+            })?;
+            impact.milestones.iter().try_for_each(|id| {
+                let milestone = project.tasks().get(id)?;
+                let new_date = impact.tasks.iter().find(|t| &t.id == id).map(|t| t.new_finish);
+                println!("Milestone delayed: {} - {} (target {}, projected {})",
+                    id, milestone.name(), milestone.target_date().unwrap(), new_date.unwrap());
+                Ok::<(), Error>(())
+            })?;
+            if !impact.sprints.is_empty() {
+                println!("Sprints affected: {}", impact.sprints.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", "));
+            }
+            if !impact.assignees.is_empty() {
+                println!("Assignees affected: {}", impact.assignees.join(", "));
+            }
+        },
         TaskCommands::GetTask { id } => {
             println!("{}", project.tasks().get(id)?);
         },
-        TaskCommands::Todo { name } => {
+        TaskCommands::Todo { name, team } => {
+            let mut todo = project.tasks().get_todo_tasks().collect::<Vec<_>>();
+            // tasks without a due date go last
+            todo.sort_by_key(|t| (t.due_date().is_none(), t.due_date().cloned(), t.id().as_vec().clone()));
+            if let Some(team) = team {
+                let owned = project.team_tasks(team)?;
+                todo.retain(|t| owned.iter().any(|o| o.id() == t.id()));
+            }
             if let Some(name) = name {
-                let member = project.members().get(name)?.clone();
-                project.tasks().get_todo_tasks().filter(|t| member.is_assigned_to(t.id())).for_each(|t| println!("{}" ,t));
+                let member = project.members().get(name)?;
+                todo.iter().filter(|t| member.is_assigned_to(t.id())).for_each(|t| println!("{}" ,t));
             } else {
-                project.tasks().get_todo_tasks().for_each(|t| println!("{}" ,t));
+                todo.iter().for_each(|t| println!("{}" ,t));
             }
         },
-        TaskCommands::AddDependency { id, dependency } => {
+        TaskCommands::AddDependency { id, dependency, kind, lag } => {
             project.tasks_mut(|tasks| {
-                tasks.add_dependency(id, dependency)?;
+                tasks.add_dependency_with(id, dependency, DependencyLink::new(*kind, *lag))?;
                 Ok(())
             })?;
         },
@@ -250,10 +926,50 @@ fn process_tasks(command: &TaskCommands, project_filename: &str) -> Result<Proje
                 Ok(())
             })?;
         },
+        TaskCommands::ReduceDependencies { dry_run } => {
+            let redundant = if *dry_run {
+                project.tasks().redundant_dependencies()?
+            } else {
+                let mut removed = vec![];
+                project.tasks_mut(|tasks| {
+                    removed = tasks.reduce_dependencies()?;
+                    Ok(())
+                })?;
+                removed
+            };
+            if redundant.is_empty() {
+                println!("No redundant dependencies");
+            }
+            let verb = if *dry_run { "Would remove" } else { "Removed" };
+            redundant.iter().for_each(|(id, dependency)| println!("{} {} -> {}", verb, id, dependency));
+        },
     };
     Ok(project)
 }
 
+fn print_dashboard(dashboard: &MemberDashboard) {
+    match dashboard.teams.as_slice() {
+        [] => println!("{}", dashboard.name),
+        teams => println!("{} ({})", dashboard.name, teams.join(", ")),
+    }
+    println!("Tasks:");
+    for task in &dashboard.tasks {
+        let due = task.due_date.map_or(String::new(), |date| format!(", due {}", date));
+        println!("  {} {} [{}] {}%{}, {}h logged", task.id, task.path.join(" > "), task.status, task.allocation, due, task.logged_hours);
+    }
+    let cost = dashboard.logged_cost.map_or("unknown (missing rate)".to_string(), |cost| cost.to_string());
+    println!("Logged: {}h, cost {}", dashboard.logged_hours, cost);
+    println!("Earned value: {}, actual cost: {}, cpi: {:.2}", dashboard.earned_value, dashboard.actual_cost, dashboard.cpi());
+    if !dashboard.upcoming_exceptions.is_empty() {
+        println!("Upcoming exceptions:");
+        dashboard.upcoming_exceptions.iter().for_each(|(i, exception)| println!("{:>3}: {}", i, exception));
+    }
+    if let Some(sprint) = &dashboard.sprint {
+        let tasks = sprint.tasks.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        println!("Sprint {} ({} to {}): [{}]", sprint.index, sprint.start, sprint.end, tasks.join(" "));
+    }
+}
+
 fn process_member(command: &MemberCommands, project_filename: &str) -> Result<Project, Error> {
     let mut project = Project::load(project_filename)?;
     Ok(match command {
@@ -271,6 +987,13 @@ fn process_member(command: &MemberCommands, project_filename: &str) -> Result<Pr
                 Ok(())
             })
         },
+        MemberCommands::Rename { old, new } => {
+            project
+            .members_mut(|members| {
+                members.rename_member(old, new)?;
+                Ok(())
+            })
+        },
         MemberCommands::Remove { name } => {
             project
             .members_mut(|members| {
@@ -278,10 +1001,75 @@ fn process_member(command: &MemberCommands, project_filename: &str) -> Result<Pr
                 Ok(())
             })
         },
-        MemberCommands::Assign { name, id } => {
+        MemberCommands::Assign { name, id, check_skills, allocation } => {
+            if *check_skills {
+                project.missing_skills(id, name)?
+                    .iter()
+                    .for_each(|(skill, level)| println!("Warning: {} lacks skill '{}' at level {}", name, skill, level));
+            }
+            project
+            .members_mut(|members| {
+                members.assign_with_allocation(id.clone(), name, *allocation)?;
+                Ok(())
+            })
+        },
+        MemberCommands::Rate { name, hourly, since } => {
+            project
+            .members_mut(|members| {
+                members.set_rate(name, since.unwrap_or_else(today), *hourly)?;
+                Ok(())
+            })
+        },
+        MemberCommands::Rates { name } => {
+            project.members().get(name)?.rates().iter().for_each(|rate| println!("{}", rate));
+            Ok(&mut project)
+        },
+        MemberCommands::RemoveRate { name, since } => {
+            project
+            .members_mut(|members| {
+                members.remove_rate(name, since)?;
+                Ok(())
+            })
+        },
+        MemberCommands::Load { from, weeks, sprints, csv, output } => {
+            let workload = if *sprints {
+                project.sprint_workload()
+            } else {
+                let from = from.unwrap_or_else(today);
+                project.weekly_workload(from, from + chrono::Duration::weeks(*weeks as i64 - 1))?
+            };
+            let s = if *csv { workload.to_csv() } else { load_heat_map(&workload) };
+            util::to_file(output.as_deref(), s)?;
+            Ok(&mut project)
+        },
+        MemberCommands::Skills { name } => {
+            let member = project.members().get(name)?;
+            println!("Roles: {}", member.roles().cloned().collect::<Vec<_>>().join(", "));
+            member.skills().iter().for_each(|(skill, level)| println!("{} - {}", skill, level));
+            Ok(&mut project)
+        },
+        MemberCommands::Skill { name, skill, level } => {
+            project
+            .members_mut(|members| {
+                members.set_skill(name, skill, *level)?;
+                Ok(())
+            })
+        },
+        MemberCommands::RemoveSkill { name, skill } => {
+            project
+            .members_mut(|members| {
+                members.remove_skill(name, skill)?;
+                Ok(())
+            })
+        },
+        MemberCommands::Role { name, role, remove } => {
             project
             .members_mut(|members| {
-                members.assign_task_to_member(id.clone(), name)?;
+                if *remove {
+                    members.remove_role(name, role)?;
+                } else {
+                    members.add_role(name, role)?;
+                }
                 Ok(())
             })
         },
@@ -292,30 +1080,388 @@ fn process_member(command: &MemberCommands, project_filename: &str) -> Result<Pr
                 Ok(())
             })
         },
-        MemberCommands::AddException { name, date, cost } => {
+        MemberCommands::AddException { name, date, cost, to, every, monthly, until, reason } => {
+            let mut exception = match (every, monthly) {
+                (Some(interval), _) => RoutineException::weekly(*date, *interval, *cost),
+                (_, Some(week)) => RoutineException::monthly(*date, *week, *cost),
+                _ => RoutineException::range(*date, to.unwrap_or(*date), *cost),
+            };
+            if let Some(until) = until {
+                exception = exception.until(*until);
+            }
+            if let Some(reason) = reason {
+                exception = exception.with_reason(reason);
+            }
+            project
+            .members_mut(|members| {
+                members.add_exception(name, exception.clone())?;
+                Ok(())
+            })
+        },
+        MemberCommands::Exceptions { name } => {
+            project.members().get(name)?
+                .routine_exceptions()
+                .iter()
+                .enumerate()
+                .for_each(|(i, exception)| println!("{:>3}: {}", i, exception));
+            Ok(&mut project)
+        },
+        MemberCommands::EditException { name, index, hours, end, reason } => {
+            let mut exception = project.members().get(name)?
+                .routine_exceptions()
+                .get(*index)
+                .cloned()
+                .ok_or_else(|| Error::RoutineExceptionNotFound(name.clone(), *index))?;
+            exception.hours = hours.unwrap_or(exception.hours);
+            exception.end = end.or(exception.end);
+            exception.reason = reason.clone().or(exception.reason);
             project
             .members_mut(|members| {
-                members.add_routine_exception(name, date, *cost)?;
+                members.edit_exception(name, *index, exception.clone())?;
                 Ok(())
             })
         },
-        MemberCommands::RemoveException { name, date } => {
+        MemberCommands::RemoveException { name, date, to, index } => {
             project
             .members_mut(|members| {
-                members.remove_routine_exception(name, date)?;
+                match (index, date) {
+                    (Some(index), _) => { members.remove_exception(name, *index)?; },
+                    (None, Some(date)) => { members.remove_routine_exceptions_between(name, date, &to.unwrap_or(*date))?; },
+                    (None, None) => unreachable!("clap requires a date or an index"),
+                }
                 Ok(())
             })
         },
+        MemberCommands::Availability { name, spec } => {
+            if let Some(spec) = spec {
+                project.members_mut(|members| {
+                    members.update_availability(name, spec)?;
+                    Ok(())
+                })?;
+            }
+            let availability = project.members().get(name)?.availability();
+            println!("{} ({}h a week)", availability, availability.weekly_hours());
+            Ok(&mut project)
+        },
+        MemberCommands::Calendar { from, to } => {
+            let from = from.unwrap_or_else(today);
+            let to = to.unwrap_or(from + chrono::Duration::days(13));
+            let capacity = project.members().capacity(&from, &to, project.calendar())?;
+            let width = capacity.iter().map(|(member, _)| member.name().len()).max().unwrap_or(0).max(6);
+            print!("{:14}", "");
+            capacity.iter().for_each(|(member, _)| print!(" {:>width$}", member.name(), width = width));
+            println!();
+            let mut reasons = vec![];
+            for day in 0..=(to - from).num_days() as usize {
+                let date = from + chrono::Duration::days(day as i64);
+                print!("{} {:3}", date, date.format("%a"));
+                for (member, days) in &capacity {
+                    let cell = match days[day].exception {
+                        Some(exception) => {
+                            if let Some(reason) = &exception.reason {
+                                reasons.push(format!("{} {}: {}", date, member.name(), reason));
+                            }
+                            format!("{}*", days[day].hours)
+                        },
+                        None => format!("{} ", days[day].hours),
+                    };
+                    print!(" {:>width$}", cell, width = width);
+                }
+                println!();
+            }
+            reasons.iter().for_each(|reason| println!("* {}", reason));
+            Ok(&mut project)
+        },
+        MemberCommands::Show { name, json } => {
+            let dashboard = project.member_dashboard(name, today())?;
+            if *json {
+                println!("{}", dashboard.to_json()?);
+            } else {
+                print_dashboard(&dashboard);
+            }
+            Ok(&mut project)
+        },
+        MemberCommands::Blocked { name } => {
+            for task in project.blocked_tasks(name)? {
+                println!("{}", project.tasks().get(&task.id)?);
+                if !task.is_blocked() {
+                    println!("  ready to start");
+                }
+                for blocker in &task.blockers {
+                    let assignees = if blocker.assignees.is_empty() { "unassigned".to_string() } else { blocker.assignees.join(", ") };
+                    println!("  waiting on {} [{}]", project.tasks().get(&blocker.id)?, assignees);
+                }
+            }
+            Ok(&mut project)
+        },
+        MemberCommands::Waiting {  } => {
+            let matrix = project.waiting_matrix();
+            let width = matrix.members.iter().map(|name| name.len()).chain(std::iter::once("unassigned".len())).max().unwrap();
+            print!("{:width$}", "", width = width);
+            matrix.members.iter().for_each(|name| print!(" {:>width$}", name, width = width));
+            println!(" {:>width$}", "unassigned", width = width);
+            for waiting in &matrix.members {
+                print!("{:width$}", waiting, width = width);
+                matrix.members.iter().for_each(|blocking| print!(" {:>width$}", matrix.waiting_on(waiting, blocking), width = width));
+                println!(" {:>width$}", matrix.waiting_on_unassigned(waiting), width = width);
+            }
+            Ok(&mut project)
+        },
     }?
     .clone())
 }
 
+/// Load of each member in each period, shaded from light to full, with `!` past 100%
+fn load_heat_map(workload: &Workload) -> String {
+    let width = workload.members.iter().map(|m| m.name.len()).max().unwrap_or(0).max("member".len());
+    let mut s = format!("{:width$}", "member", width = width);
+    workload.periods.iter().for_each(|(start, _)| s += &format!(" {:>6}", start.format("%m-%d")));
+    s += "  unscheduled\n";
+    for member in &workload.members {
+        s += &format!("{:width$}", member.name, width = width);
+        for i in 0..workload.periods.len() {
+            let load = member.load(i);
+            let shade = match load {
+                l if l > 1.0 + 1e-9 => '!',
+                l if l >= 0.8 => '█',
+                l if l >= 0.5 => '▓',
+                l if l > 0.0 => '▒',
+                _ => '░',
+            };
+            let percent = if load.is_finite() { format!("{:.0}%", load * 100.0) } else { "∞".to_string() };
+            s += &format!(" {:>5}{}", percent, shade);
+        }
+        s += &format!("  {:.1}h\n", member.unscheduled);
+    }
+    s += "░ idle  ▒ under 50%  ▓ under 80%  █ up to 100%  ! over-allocated\n";
+    for (name, periods) in workload.over_allocated() {
+        let starts = periods.iter().map(|i| workload.periods[*i].0.to_string()).collect::<Vec<_>>();
+        s += &format!("{} is over-allocated in the periods starting {}\n", name, starts.join(", "));
+    }
+    s
+}
+
+fn process_milestones(project_filename: &str) -> Result<Project, Error> {
+    let project = Project::load(project_filename)?;
+    let today = today();
+    let mut milestones = project.tasks().get_milestones().collect::<Vec<_>>();
+    milestones.sort_by_key(|task| task.target_date());
+    milestones.iter().for_each(|task| {
+        println!("{} - {} - {} - {}", task.id(), task.name(), task.target_date().unwrap(), task.milestone_status(&today).unwrap());
+    });
+    Ok(project)
+}
+
+fn assignee_names(project: &Project, id: &TaskId) -> String {
+    project.members()
+        .assignees(id)
+        .map(|m| match m.allocation(id) {
+            Some(allocation) if allocation < 100 => format!("{} {}%", m.name(), allocation),
+            _ => m.name().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn process_overdue(days: i64, project_filename: &str) -> Result<Project, Error> {
+    let project = Project::load(project_filename)?;
+    let today = today();
+    let print_tasks = |title: &str, mut tasks: Vec<&Task>| {
+        tasks.sort_by_key(|t| t.due_date().cloned());
+        println!("{}:", title);
+        tasks.iter().for_each(|t| println!("  {} - [{}]", t, assignee_names(&project, t.id())));
+    };
+    print_tasks("Late", project.tasks().get_late_tasks(&today).collect());
+    print_tasks("Due soon", project.tasks().get_due_soon_tasks(&today, days).collect());
+    Ok(project)
+}
+
+fn process_simulate(start: Option<NaiveDate>, iterations: usize, seed: u64, top: usize, project_filename: &str) -> Result<Project, Error> {
+    let project = Project::load(project_filename)?;
+    let mut options = SimulationOptions::new(start.unwrap_or_else(today));
+    options.iterations = iterations;
+    options.seed = seed;
+    options.calendar = project.calendar().clone();
+    let report = project.tasks().simulate(&options);
+    println!("P50: {}", report.p50);
+    println!("P80: {}", report.p80);
+    println!("P95: {}", report.p95);
+    println!("Critical path frequency:");
+    report.criticality.iter().take(top).try_for_each(|(id, frequency)| {
+        println!("  {:5.1}% {}", frequency * 100.0, project.tasks().get(id)?);
+        Ok(())
+    })?;
+    Ok(project)
+}
+
+fn process_next(name: Option<&str>, order: NextOrder, project_filename: &str) -> Result<Project, Error> {
+    let project = Project::load(project_filename)?;
+    project.ready_queue(name, order.into())?
+        .iter()
+        .for_each(|t| println!("{} - [{}]", t, assignee_names(&project, t.id())));
+    Ok(project)
+}
+
+fn process_schedule(start: Option<NaiveDate>, project_filename: &str) -> Result<Project, Error> {
+    let mut project = Project::load(project_filename)?;
+    let before = project.tasks().get_tasks()
+        .map(|t| (t.id().clone(), t.planned_dates()))
+        .collect::<HashMap<_, _>>();
+    project.tasks_mut(|tasks| {
+        tasks.level(start.unwrap_or_else(today))?;
+        Ok(())
+    })?;
+    let mut moved = project.tasks().get_tasks()
+        .filter(|t| before.get(t.id()).is_some_and(|dates| *dates != t.planned_dates()))
+        .collect::<Vec<_>>();
+    moved.sort_by_key(|t| (t.planned_dates(), t.id().as_vec().clone()));
+    let dates_str = |dates: Option<(NaiveDate, NaiveDate)>| dates
+        .map_or("unplanned".to_string(), |(start, finish)| format!("{} → {}", start, finish));
+    moved.iter().for_each(|t| {
+        println!("{} - {}: {} -> {}", t.id(), t.name(), dates_str(before[t.id()]), dates_str(t.planned_dates()));
+    });
+    if moved.is_empty() {
+        println!("Nothing moved");
+    }
+    if let Some((_, finish)) = project.tasks().planned_dates(&TaskId::get_root_id()) {
+        println!("Planned finish: {}", finish);
+    }
+    Ok(project)
+}
+
+fn process_holiday(command: &HolidayCommands, project_filename: &str) -> Result<Project, Error> {
+    let mut project = Project::load(project_filename)?;
+    match command {
+        HolidayCommands::Add { date } => {
+            project.add_holiday(*date)?;
+        },
+        HolidayCommands::Remove { date } => {
+            project.remove_holiday(date)?;
+        },
+        HolidayCommands::List {  } => {
+            project.calendar().holidays().for_each(|date| println!("{} {}", date, date.weekday()));
+        }
+    }
+    Ok(project)
+}
+
+fn process_team(command: &TeamCommands, project_filename: &str) -> Result<Project, Error> {
+    let mut project = Project::load(project_filename)?;
+    match command {
+        TeamCommands::List {  } => {
+            project.members().teams().for_each(|team| println!("{}", team));
+        },
+        TeamCommands::Add { name } => {
+            project.members_mut(|members| {
+                members.add_team(name)?;
+                Ok(())
+            })?;
+        },
+        TeamCommands::Remove { name } => {
+            project.members_mut(|members| {
+                members.remove_team(name)?;
+                Ok(())
+            })?;
+        },
+        TeamCommands::Join { team, member } => {
+            project.members_mut(|members| {
+                members.join_team(team, member)?;
+                Ok(())
+            })?;
+        },
+        TeamCommands::Leave { team, member } => {
+            project.members_mut(|members| {
+                members.leave_team(team, member)?;
+                Ok(())
+            })?;
+        },
+        TeamCommands::Assign { team, id } => {
+            project.members_mut(|members| {
+                members.assign_team(id.clone(), team)?;
+                Ok(())
+            })?;
+        },
+        TeamCommands::Unassign { id } => {
+            project.members_mut(|members| {
+                members.unassign_team(id)?;
+                Ok(())
+            })?;
+        },
+        TeamCommands::Capacity { team, from, to } => {
+            let from = from.unwrap_or_else(today);
+            let to = to.unwrap_or(from + chrono::Duration::days(13));
+            for (member, days) in project.members().team_capacity(team, &from, &to, project.calendar())? {
+                println!("{}: {}h", member.name(), days.iter().fold(0.0, |total, day| total + day.hours));
+            }
+            println!("Total from {} to {}: {}h", from, to, project.members().total_team_capacity(team, &from, &to, project.calendar())?);
+        },
+        TeamCommands::Metrics { team } => {
+            let metrics = project.team_metrics(team)?;
+            for id in &metrics.tasks {
+                println!("{}", project.tasks().get(id)?);
+            }
+            println!(
+                "planned value: {}, actual cost: {}, earned value: {}, spi: {:.2}, sv: {}, cpi: {:.2}, cv: {}",
+                metrics.planned_value,
+                metrics.actual_cost,
+                metrics.earned_value,
+                metrics.spi(),
+                metrics.sv(),
+                metrics.cpi(),
+                metrics.cv());
+        }
+    }
+    Ok(project)
+}
+
+fn process_check(fix: bool, project_filename: &str) -> Result<Project, Error> {
+    let mut project = Project::load(project_filename)?;
+    let violations = project.validate();
+    violations.iter().for_each(|v| {
+        let fixable = if v.is_fixable() { " (fixable)" } else { "" };
+        println!("{}{}", v, fixable);
+    });
+    if fix {
+        let remaining = project.repair();
+        // repairing can bring up new problems, so count the ones that went away
+        let fixed = violations.iter().filter(|v| !remaining.contains(v)).count();
+        println!("Fixed {} problem(s), {} remaining", fixed, remaining.len());
+    } else if violations.is_empty() {
+        println!("No problems found");
+    }
+    Ok(project)
+}
+
 fn process_args(cli: Cli) -> Result<(), Error>  {
 
     match &cli.command {
         Commands::Init { project } => {
             Project::new(project)
         },
+        Commands::Milestones {  } => {
+            process_milestones(&cli.filename)?
+        },
+        Commands::Overdue { days } => {
+            process_overdue(*days, &cli.filename)?
+        },
+        Commands::Simulate { start, iterations, seed, top } => {
+            process_simulate(*start, *iterations, *seed, *top, &cli.filename)?
+        },
+        Commands::Next { name, by } => {
+            process_next(name.as_deref(), *by, &cli.filename)?
+        },
+        Commands::Schedule { start } => {
+            process_schedule(*start, &cli.filename)?
+        },
+        Commands::Holiday { command } => {
+            process_holiday(command, &cli.filename)?
+        },
+        Commands::Team { command } => {
+            process_team(command, &cli.filename)?
+        },
+        Commands::Check { fix } => {
+            process_check(*fix, &cli.filename)?
+        },
         Commands::Member { command } => {
             process_member(command, &cli.filename)?
         },
@@ -333,3 +1479,18 @@ fn main() {
         println!("Error: {}", e);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monthly_exception_on_last_weekday() {
+        let cli = Cli::try_parse_from(["aplan", "member", "add-exception", "alice", "29/01/2024", "0", "--monthly", "-1"]).unwrap();
+        match cli.command {
+            Commands::Member { command: MemberCommands::AddException { monthly, .. } } => assert_eq!(monthly, Some(-1)),
+            _ => panic!("expected add-exception"),
+        }
+        assert!(Cli::try_parse_from(["aplan", "member", "add-exception", "alice", "29/01/2024", "0", "--monthly", "-2"]).is_err());
+    }
+}
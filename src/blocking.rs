@@ -0,0 +1,163 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::prelude::{Project, TaskId, TaskStatus, Error};
+
+/// Unfinished dependency of a task, with the members that can unblock it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Blocker {
+    pub id: TaskId,
+    /// Sorted by name, empty if nobody is assigned
+    pub assignees: Vec<String>,
+}
+
+/// Unfinished task of a member, along with the dependencies it is waiting on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockedTask {
+    pub id: TaskId,
+    /// Empty if the task can start
+    pub blockers: Vec<Blocker>,
+}
+
+impl BlockedTask {
+    pub fn is_blocked(&self) -> bool {
+        !self.blockers.is_empty()
+    }
+}
+
+/// How many tasks of each member are waiting on tasks of each other member
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WaitingMatrix {
+    /// Sorted by name
+    pub members: Vec<String>,
+    waiting: HashMap<(String, String), usize>,
+    unassigned: HashMap<String, usize>,
+}
+
+impl WaitingMatrix {
+    /// Number of tasks of `waiting` blocked by at least one task of `blocking`
+    pub fn waiting_on(&self, waiting: &str, blocking: &str) -> usize {
+        self.waiting.get(&(waiting.to_string(), blocking.to_string())).cloned().unwrap_or(0)
+    }
+
+    /// Number of tasks of `waiting` blocked by at least one task nobody is assigned to
+    pub fn waiting_on_unassigned(&self, waiting: &str) -> usize {
+        self.unassigned.get(waiting).cloned().unwrap_or(0)
+    }
+}
+
+impl Project {
+
+    /// Unfinished leaves assigned to member `name`, in id order, each with the unfinished
+    /// dependencies it is waiting on. Dependencies on trunks count as every unfinished leaf
+    /// under them.
+    pub fn blocked_tasks(&self, name: &str) -> Result<Vec<BlockedTask>, Error> {
+        let member = self.members.get(name)?;
+        let mut ids = member
+            .task_ids()
+            .filter(|id| self.tasks.get(id).is_ok_and(|task| task.status != TaskStatus::Done))
+            .collect::<Vec<_>>();
+        ids.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+        Ok(ids
+            .into_iter()
+            .map(|id| BlockedTask { id: id.clone(), blockers: self.blockers(id) })
+            .collect())
+    }
+
+    fn blockers(&self, id: &TaskId) -> Vec<Blocker> {
+        let mut dependencies = self.tasks
+            .leaf_dependencies(id)
+            .into_iter()
+            .map(|(dependency_id, _)| dependency_id)
+            .filter(|dependency_id| self.tasks.get(dependency_id).is_ok_and(|task| task.status != TaskStatus::Done))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        dependencies.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+        dependencies
+            .into_iter()
+            .map(|dependency_id| {
+                let mut assignees = self.members
+                    .assignees(dependency_id)
+                    .map(|member| member.name().to_string())
+                    .collect::<Vec<_>>();
+                assignees.sort();
+                Blocker { id: dependency_id.clone(), assignees }
+            })
+            .collect()
+    }
+
+    /// Who is waiting on whom, across the whole team
+    pub fn waiting_matrix(&self) -> WaitingMatrix {
+        let mut members = self.members
+            .members()
+            .map(|member| member.name().to_string())
+            .collect::<Vec<_>>();
+        members.sort();
+
+        let mut waiting = HashMap::new();
+        let mut unassigned = HashMap::new();
+        for name in &members {
+            // SAFETY: names come from the members themselves
+            for task in self.blocked_tasks(name).unwrap() {
+                let blocking = task.blockers
+                    .iter()
+                    .flat_map(|blocker| &blocker.assignees)
+                    .collect::<HashSet<_>>();
+                blocking
+                    .into_iter()
+                    .for_each(|blocking| *waiting.entry((name.clone(), blocking.clone())).or_insert(0) += 1);
+                if task.blockers.iter().any(|blocker| blocker.assignees.is_empty()) {
+                    *unassigned.entry(name.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+        WaitingMatrix { members, waiting, unassigned }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocked_tasks() {
+        let mut project = Project::new("Project");
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Backend"),
+                    ("1", "Database"),
+                    ("1", "API"),
+                ("", "Frontend"),
+                ("", "Docs"),
+            ])?;
+            tasks.add_dependency(&TaskId::parse("2")?, &TaskId::parse("1")?)?;
+            tasks.add_dependency(&TaskId::parse("3")?, &TaskId::parse("2")?)?;
+            tasks.done(&TaskId::parse("1.1")?, 1.0)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.assign_task_to_member(TaskId::parse("1.1")?, "alice")?;
+            members.assign_task_to_member(TaskId::parse("2")?, "bob")?;
+            members.assign_task_to_member(TaskId::parse("3")?, "alice")?;
+            Ok(())
+        }).unwrap();
+
+        // the database is done, so only the unassigned API blocks the frontend
+        let blocked = project.blocked_tasks("bob").unwrap();
+        assert_eq!(blocked, vec![BlockedTask {
+            id: TaskId::parse("2").unwrap(),
+            blockers: vec![Blocker { id: TaskId::parse("1.2").unwrap(), assignees: vec![] }]
+        }]);
+        let blocked = project.blocked_tasks("alice").unwrap();
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].blockers[0].assignees, vec!["bob".to_string()]);
+
+        let matrix = project.waiting_matrix();
+        assert_eq!(matrix.members, vec!["alice".to_string(), "bob".to_string()]);
+        assert_eq!(matrix.waiting_on("alice", "bob"), 1);
+        assert_eq!(matrix.waiting_on("bob", "alice"), 0);
+        assert_eq!(matrix.waiting_on_unassigned("bob"), 1);
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::BTreeSet;
+
+use chrono::{NaiveDate, Datelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Working days of the project: Monday to Friday, except holidays
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Calendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl Calendar {
+
+    pub fn holidays(&self) -> impl Iterator<Item=&NaiveDate> {
+        self.holidays.iter()
+    }
+
+    pub fn is_holiday(&self, date: &NaiveDate) -> bool {
+        self.holidays.contains(date)
+    }
+
+    pub fn is_working_day(&self, date: &NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.is_holiday(date)
+    }
+
+    /// Last day of `days` working days of work begun on `start`, or `start` itself if there is
+    /// no work
+    pub fn finish_date(&self, start: NaiveDate, days: u32) -> NaiveDate {
+        let (mut date, mut left) = (start, days);
+        while left > 0 {
+            if self.is_working_day(&date) {
+                left -= 1;
+            }
+            if left > 0 {
+                date = date.succ();
+            }
+        }
+        date
+    }
+
+    /// Returns false if `date` already was a holiday
+    pub(crate) fn add_holiday(&mut self, date: NaiveDate) -> bool {
+        self.holidays.insert(date)
+    }
+
+    /// Returns false if `date` wasn't a holiday
+    pub(crate) fn remove_holiday(&mut self, date: &NaiveDate) -> bool {
+        self.holidays.remove(date)
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::NaiveDate;
+
+use crate::prelude::{Project, TaskId, Error};
+
+impl Project {
+
+    /// Cost of the hours logged on leaf `id`, each at the rate its member had on the day
+    pub fn logged_cost(&self, id: &TaskId) -> Result<f64, Error> {
+        self.tasks
+            .get(id)?
+            .work_logs()
+            .iter()
+            .map(|log| {
+                let rate = self.members
+                    .get(&log.member)?
+                    .rate_on(&log.date)
+                    .ok_or_else(|| Error::NoRate(log.member.clone(), log.date))?;
+                Ok(log.hours * rate)
+            })
+            .sum()
+    }
+
+    /// Cost of the estimated effort of leaf `id`, each assignee doing their share of it at their
+    /// rate on the planned start of the leaf, or on `as_of` if it isn't planned. None if the leaf
+    /// has no estimate or nobody is assigned to it.
+    pub fn estimated_cost(&self, id: &TaskId, as_of: NaiveDate) -> Result<Option<f64>, Error> {
+        let task = self.tasks.get(id)?;
+        if task.estimate().is_none() || !self.members.is_assigned(id) {
+            return Ok(None);
+        }
+        let date = task.planned_dates().map_or(as_of, |(start, _)| start);
+        let rate = self.members
+            .assignees(id)
+            .map(|member| {
+                let rate = member.rate_on(&date).ok_or_else(|| Error::NoRate(member.name().to_string(), date))?;
+                Ok(rate * self.members.share(id, member.name()))
+            })
+            .sum::<Result<f64, Error>>()?;
+        Ok(Some(task.expected_effort() * rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Estimate, TaskStatus, WorkLog};
+
+    #[test]
+    fn derived_costs() {
+        let mut project = Project::new("Project");
+        let (design, build) = (TaskId::parse("1").unwrap(), TaskId::parse("2").unwrap());
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.set_rate("alice", NaiveDate::from_ymd(2022, 1, 1), 50.0)?;
+            members.set_rate("alice", NaiveDate::from_ymd(2022, 7, 1), 60.0)?;
+            members.set_rate("bob", NaiveDate::from_ymd(2022, 1, 1), 40.0)?;
+            Ok(())
+        }).unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Design"),
+                ("", "Build"),
+            ])?;
+            tasks.estimate(&design, Some(Estimate::new(10.0)?))?;
+            tasks.estimate(&build, Some(Estimate::new(20.0)?))?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.assign_with_allocation(design.clone(), "alice", 50)?;
+            members.assign_with_allocation(design.clone(), "bob", 50)?;
+            members.assign_task_to_member(build.clone(), "alice")?;
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(project.estimated_cost(&design, NaiveDate::from_ymd(2022, 3, 1)), Ok(Some(450.0)));
+        assert_eq!(project.estimated_cost(&design, NaiveDate::from_ymd(2021, 3, 1)), Err(Error::NoRate("alice".to_string(), NaiveDate::from_ymd(2021, 3, 1))));
+
+        project.tasks_mut(|tasks| {
+            tasks.log_work(&design, "alice", NaiveDate::from_ymd(2022, 6, 30), 4.0)?;
+            tasks.log_work(&design, "alice", NaiveDate::from_ymd(2022, 7, 1), 2.0)?;
+            tasks.planned_value(&build, 1500.0)?;
+            tasks.derive_costs(NaiveDate::from_ymd(2022, 8, 1))?;
+            Ok(())
+        }).unwrap();
+        assert!(project.tasks_mut(|tasks| {
+            tasks.log_work(&design, "bob", NaiveDate::from_ymd(2021, 12, 1), 1.0)?;
+            Ok(())
+        }).is_err());
+
+        // logged hours are priced at the rate of their day, and rolled up like any other cost
+        assert_eq!(project.tasks().get(&design).unwrap().actual_cost, 320.0);
+        assert_eq!(project.tasks().actual_cost(), 320.0);
+        assert_eq!(project.tasks().get(&design).unwrap().status, TaskStatus::InProgress);
+        // the planned value of the build was set by hand, so it stays
+        assert_eq!(project.tasks().get(&design).unwrap().planned_value, 500.0);
+        assert_eq!(project.tasks().get(&build).unwrap().planned_value, 1500.0);
+        assert_eq!(project.tasks().planned_value(), 2000.0);
+
+        project.tasks_mut(|tasks| {
+            tasks.reset_cost_overrides(&build)?;
+            tasks.derive_costs(NaiveDate::from_ymd(2022, 8, 1))?;
+            tasks.done_from_logs(&design)?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(project.tasks().get(&build).unwrap().planned_value, 1200.0);
+        assert_eq!(project.tasks().get(&design).unwrap().status, TaskStatus::Done);
+        assert_eq!(project.tasks().actual_cost(), 320.0);
+
+        // a leaf whose assignee has no rate is skipped rather than failing the whole run, and
+        // keeps the values it had, like the ones of a file whose rates were lost
+        let docs = TaskId::parse("3").unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.add(TaskId::get_root_id(), "Docs")?;
+            tasks.estimate(&docs, Some(Estimate::new(5.0)?))?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("carol")?;
+            members.assign_task_to_member(docs.clone(), "carol")?;
+            Ok(())
+        }).unwrap();
+        project.tasks.set_planned_value(&docs, 250.0).unwrap();
+        project.tasks.update_actual_cost(&docs, 90.0).unwrap();
+        project.tasks.add_work_log(&docs, WorkLog { member: "carol".to_string(), date: NaiveDate::from_ymd(2022, 7, 4), hours: 3.0 }).unwrap();
+        // values of files saved before they could be derived count as set by hand
+        project.tasks.get_mut(&build).unwrap().manual_planned_value = None;
+        project.tasks_mut(|tasks| {
+            tasks.derive_costs(NaiveDate::from_ymd(2022, 8, 1))?;
+            Ok(())
+        }).unwrap();
+        let task = project.tasks().get(&docs).unwrap();
+        assert_eq!((task.planned_value, task.actual_cost, task.logged_hours()), (250.0, 90.0, 3.0));
+        assert!(project.tasks().get(&build).unwrap().has_manual_planned_value());
+    }
+}
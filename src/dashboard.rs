@@ -0,0 +1,213 @@
+use chrono::{NaiveDate, Duration};
+use serde::{Serialize, Serializer};
+
+use crate::prelude::{Project, TaskId, TaskStatus, RoutineException, Error};
+
+/// What a member is working on and how it is going, as of a given day
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MemberDashboard {
+    pub name: String,
+    /// Teams the member is in, sorted by name
+    pub teams: Vec<String>,
+    /// Leaves the member is assigned to, sorted by id
+    pub tasks: Vec<AssignedTask>,
+    /// Hours the member logged, on any task
+    pub logged_hours: f64,
+    /// Cost of those hours, each at the rate the member had on the day. None if a rate is missing.
+    pub logged_cost: Option<f64>,
+    /// Share of the planned value of the done leaves that falls on the member
+    pub earned_value: f64,
+    /// Share of the actual cost of the leaves that falls on the member
+    pub actual_cost: f64,
+    /// Routine exceptions that still apply on the day or later, with their index in
+    /// `Member::routine_exceptions`
+    pub upcoming_exceptions: Vec<(usize, RoutineException)>,
+    /// Sprint running on the day, if any
+    pub sprint: Option<SprintCommitment>,
+}
+
+/// Leaf a member is assigned to
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AssignedTask {
+    #[serde(serialize_with = "serialize_id")]
+    pub id: TaskId,
+    /// Names of the ancestors of the task, from the top of the WBS, and of the task itself
+    pub path: Vec<String>,
+    pub status: TaskStatus,
+    /// Percentage of the task the member does
+    pub allocation: u32,
+    pub due_date: Option<NaiveDate>,
+    /// Hours the member logged on the task
+    pub logged_hours: f64,
+}
+
+/// Leaves of a member in the backlog of a sprint, directly or through an ancestor
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SprintCommitment {
+    pub index: usize,
+    /// First day of the sprint
+    pub start: NaiveDate,
+    /// Last day of the sprint
+    pub end: NaiveDate,
+    #[serde(serialize_with = "serialize_ids")]
+    pub tasks: Vec<TaskId>,
+}
+
+/// Ids are written the way they are typed, like `1.2`, for scripts to use them as is
+fn serialize_id<S: Serializer>(id: &TaskId, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(id)
+}
+
+fn serialize_ids<S: Serializer>(ids: &[TaskId], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(ids.iter().map(|id| id.to_string()))
+}
+
+impl MemberDashboard {
+    /// Earned value over actual cost of the member's share of their leaves, 0 if undefined
+    pub fn cpi(&self) -> f64 {
+        let res = self.earned_value / self.actual_cost;
+        if res.is_nan() { 0.0 } else { res }
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self)
+            .map_err(|_| Error::SerializeDashboard(self.name.clone()))
+    }
+}
+
+impl Project {
+
+    /// Dashboard of member `name` as of `as_of`
+    pub fn member_dashboard(&self, name: &str, as_of: NaiveDate) -> Result<MemberDashboard, Error> {
+        let member = self.members.get(name)?;
+        let mut ids = member.task_ids().collect::<Vec<_>>();
+        ids.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+
+        let mut tasks = Vec::with_capacity(ids.len());
+        let (mut earned_value, mut actual_cost) = (0.0, 0.0);
+        for id in ids {
+            let task = self.tasks.get(id)?;
+            let share = self.members.share(id, name);
+            actual_cost += task.get_actual_cost() * share;
+            if task.status == TaskStatus::Done {
+                earned_value += task.get_planned_value() * share;
+            }
+            tasks.push(AssignedTask {
+                id: id.clone(),
+                path: id.path().skip(1).map(|id| Ok(self.tasks.get(&id)?.name().to_string())).collect::<Result<_, Error>>()?,
+                status: task.status.clone(),
+                allocation: member.allocation(id).unwrap_or_default(),
+                due_date: task.due_date().copied(),
+                logged_hours: task.work_logs().iter().filter(|log| log.member == name).fold(0.0, |total, log| total + log.hours),
+            });
+        }
+
+        let logs = self.tasks
+            .get_tasks()
+            .flat_map(|task| task.work_logs())
+            .filter(|log| log.member == name)
+            .collect::<Vec<_>>();
+        let logged_cost = logs
+            .iter()
+            .map(|log| member.rate_on(&log.date).map(|rate| log.hours * rate))
+            .try_fold(0.0, |total, cost| cost.map(|cost| total + cost));
+
+        let sprint = self.sprints.sprint_on(&as_of).map(|index| {
+            let (start, end) = self.sprints.sprint_to_date_time(index);
+            let backlog = self.sprints.sprints()[index].backlog();
+            SprintCommitment {
+                index,
+                start: start.naive_utc(),
+                end: end.naive_utc() - Duration::days(1),
+                tasks: tasks
+                    .iter()
+                    .filter(|task| task.id.path().any(|id| backlog.contains(&id)))
+                    .map(|task| task.id.clone())
+                    .collect(),
+            }
+        });
+
+        Ok(MemberDashboard {
+            name: name.to_string(),
+            teams: self.members.teams_of(name).map(|team| team.name().to_string()).collect(),
+            logged_hours: logs.iter().fold(0.0, |total, log| total + log.hours),
+            logged_cost,
+            earned_value,
+            actual_cost,
+            upcoming_exceptions: member
+                .routine_exceptions()
+                .iter()
+                .enumerate()
+                .filter(|(_, exception)| exception.last_day().is_none_or(|end| end >= as_of))
+                .map(|(index, exception)| (index, exception.clone()))
+                .collect(),
+            sprint,
+            tasks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn member_dashboard() {
+        let mut project = Project::new("Project");
+        let (api, ui) = (TaskId::parse("1.1").unwrap(), TaskId::parse("1.2").unwrap());
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Build"),
+                ("1", "API"),
+                ("1", "UI"),
+            ])?;
+            tasks.planned_value(&api, 1000.0)?;
+            tasks.planned_value(&ui, 400.0)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.add_team("backend")?;
+            members.join_team("backend", "alice")?;
+            members.set_rate("alice", NaiveDate::from_ymd(2024, 1, 1), 50.0)?;
+            members.assign_with_allocation(api.clone(), "alice", 50)?;
+            members.assign_with_allocation(api.clone(), "bob", 50)?;
+            members.assign_task_to_member(ui.clone(), "alice")?;
+            members.add_exception("alice", RoutineException::range(NaiveDate::from_ymd(2024, 1, 2), NaiveDate::from_ymd(2024, 1, 3), 0.0))?;
+            members.add_exception("alice", RoutineException::weekly(NaiveDate::from_ymd(2024, 1, 5), 1, 4.0))?;
+            Ok(())
+        }).unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.log_work(&api, "alice", NaiveDate::from_ymd(2024, 1, 8), 6.0)?;
+            tasks.done(&api, 800.0)?;
+            Ok(())
+        }).unwrap();
+        project.sprints.set_schedule(Utc.ymd(2024, 1, 1).and_hms(0, 0, 0), 2);
+        project.sprints.push(vec![]);
+        project.sprints.push(vec![TaskId::parse("1").unwrap()]);
+
+        let dashboard = project.member_dashboard("alice", NaiveDate::from_ymd(2024, 1, 16)).unwrap();
+        assert_eq!(dashboard.teams, vec!["backend"]);
+        assert_eq!(dashboard.tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>(), vec![api.clone(), ui.clone()]);
+        assert_eq!(dashboard.tasks[0].path, vec!["Build", "API"]);
+        assert_eq!((dashboard.tasks[0].allocation, dashboard.tasks[0].logged_hours), (50, 6.0));
+        assert_eq!((dashboard.logged_hours, dashboard.logged_cost), (6.0, Some(300.0)));
+        // half of the API is hers, and it cost less than planned
+        assert_eq!((dashboard.earned_value, dashboard.actual_cost), (500.0, 400.0));
+        assert_eq!(dashboard.cpi(), 1.25);
+        assert_eq!(dashboard.upcoming_exceptions.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(dashboard.sprint, Some(SprintCommitment {
+            index: 1,
+            start: NaiveDate::from_ymd(2024, 1, 15),
+            end: NaiveDate::from_ymd(2024, 1, 28),
+            tasks: vec![api, ui],
+        }));
+        let json = dashboard.to_json().unwrap();
+        assert!(json.contains("\"id\": \"1.1\""));
+        assert!(json.contains("\"Build\",\n        \"API\""));
+        assert!(project.member_dashboard("carol", NaiveDate::from_ymd(2024, 1, 16)).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::NaiveDate;
+
 use crate::task::task_id::TaskId;
 
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
@@ -18,6 +20,27 @@ pub enum Error {
     #[error("Member '{0}' not found")]
     MemberNotFound(String),
 
+    #[error("Member '{0}' already exists")]
+    MemberAlreadyExists(String),
+
+    #[error("Can't remove member '{0}', who logged hours on '{1}'")]
+    MemberHasLoggedHours(String, TaskId),
+
+    #[error("Team '{0}' not found")]
+    TeamNotFound(String),
+
+    #[error("Team '{0}' already exists")]
+    TeamAlreadyExists(String),
+
+    #[error("Member '{1}' is not in team '{0}'")]
+    NotInTeam(String, String),
+
+    #[error("Task '{0}' is not assigned to a team")]
+    NoTeamAssigned(TaskId),
+
+    #[error("Can't assign trunk tasks like '{0}' to a team directly")]
+    TrunkCannotAssignTeam(TaskId),
+
     #[error("Couldn't open file '{0}'")]
     OpenFile(String),
 
@@ -36,20 +59,83 @@ pub enum Error {
     #[error("Trunk tasks like '{0}' cannot be removed")]
     TrunkCannotBeRemoved(TaskId),
 
-    #[error("Can't have trunk like '{0}' as dependency")]
-    TrunkCannotBeDependency(TaskId),
+    #[error("Trunk tasks like '{0}' cannot be split")]
+    TrunkCannotBeSplit(TaskId),
 
-    #[error("Trunks like '{0}' can't have dependencies")]
-    TrunkCannotHaveDependency(TaskId),
+    #[error("Can't split task '{0}' without giving at least one subtask")]
+    SplitWithoutSubtasks(TaskId),
 
-    #[error("Can't convert '{0}' to trunk because it has or is a dependency")]
-    CannotConvertToTrunk(TaskId),
+    #[error("Can't move dependencies of '{0}' to subtask number {1}, since it doesn't exist")]
+    BadSplitTarget(TaskId, u32),
+
+    #[error("Split amounts for '{0}' must have one non-negative value per subtask and add up to the original value")]
+    SplitAmountsMismatch(TaskId),
 
     #[error("Cannot remove node '{0}' with dependencies")]
     CannotRemoveWithDependency(TaskId),
 
-    #[error("Creating this dependency edge ['{0}' -> '{1}'] would create a cycle")]
-    EdgeCreationLeadsToCycle(TaskId, TaskId),
+    #[error("Creating this dependency edge ['{0}' -> '{1}'] would create a cycle: {}", cycle_str(.2))]
+    EdgeCreationLeadsToCycle(TaskId, TaskId, Vec<TaskId>),
+
+    #[error("'{0}' is not a valid availability, expected something like 'mon-thu=8,fri=4' with 0 to 24 hours a day")]
+    BadAvailability(String),
+
+    #[error("Date range from {0} to {1} ends before it starts")]
+    BadDateRange(NaiveDate, NaiveDate),
+
+    #[error("'{0}' is not a valid routine exception")]
+    BadRoutineException(String),
+
+    #[error("Member '{0}' has no routine exception number {1}")]
+    RoutineExceptionNotFound(String, usize),
+
+    #[error("'{0}' is not a valid proficiency (beginner, intermediate, advanced, expert or 1 to 4)")]
+    BadProficiency(String),
+
+    #[error("Member '{0}' has no role '{1}'")]
+    RoleNotFound(String, String),
+
+    #[error("Member '{0}' has no skill '{1}'")]
+    SkillNotFound(String, String),
+
+    #[error("Task '{0}' doesn't require skill '{1}'")]
+    SkillNotRequired(TaskId, String),
+
+    #[error("Trunk '{0}' cannot require skills, only its leaves can")]
+    TrunkCannotRequireSkill(TaskId),
+
+    #[error("Trunk '{0}' cannot have a priority, only its leaves can")]
+    TrunkCannotHavePriority(TaskId),
+
+    #[error("Allocation of {0}% is not valid, it must be from 1 to 100%")]
+    BadAllocation(u32),
+
+    #[error("Allocations on '{0}' would add up to {1}%, over 100%")]
+    OverAllocatedTask(TaskId, u32),
+
+    #[error("'{0}' is not a valid hourly rate")]
+    BadRate(String),
+
+    #[error("Member '{0}' has no rate starting on {1}")]
+    RateNotFound(String, NaiveDate),
+
+    #[error("Member '{0}' has no hourly rate on {1}")]
+    NoRate(String, NaiveDate),
+
+    #[error("'{0}' is not a valid number of hours to log")]
+    BadWorkLog(String),
+
+    #[error("{0} is already a holiday")]
+    HolidayAlreadyExists(NaiveDate),
+
+    #[error("{0} is not a holiday")]
+    HolidayNotFound(NaiveDate),
+
+    #[error("Task '{0}' cannot be scheduled: its assignees have no time left in the next {1} days")]
+    CannotSchedule(TaskId, i64),
+
+    #[error("'{0}' is not a valid dependency type (FS, SS, FF or SF)")]
+    BadDependencyType(String),
 
     #[error("Root task '{0}' doesn't have a parent")]
     NoParent(TaskId),
@@ -63,6 +149,15 @@ pub enum Error {
     #[error("Can't change planned value of trunk tasks like '{0}' directly")]
     TrunkCannotChangeValue(TaskId),
 
+    #[error("Can't change due date of trunk tasks like '{0}' directly")]
+    TrunkCannotChangeDueDate(TaskId),
+
+    #[error("Can't change estimate of trunk tasks like '{0}' directly")]
+    TrunkCannotChangeEstimate(TaskId),
+
+    #[error("Estimate '{0}' must not be negative and must be in optimistic/most likely/pessimistic order")]
+    BadEstimate(String),
+
     #[error("Can't add members to trunk tasks like '{0}' directly")]
     TrunkCannotAddMember(TaskId),
 
@@ -72,12 +167,24 @@ pub enum Error {
     #[error("Can't remove tasks with members assigned to it")]
     CannotRemoveAssignedTask(TaskId),
 
+    #[error("Milestones like '{0}' can't have subtasks")]
+    MilestoneCannotHaveChildren(TaskId),
+
+    #[error("Milestones like '{0}' have no planned value or actual cost")]
+    MilestoneCannotHaveEffort(TaskId),
+
+    #[error("Task '{0}' is not a milestone")]
+    NotAMilestone(TaskId),
+
     #[error("Couldn't parse JSON to project: '{0}'")]
     ParseJsonContents(String),
 
     #[error("Couldn't parse project to JSON")]
     ParseProjectContents,
 
+    #[error("Couldn't write the dashboard of '{0}' as JSON")]
+    SerializeDashboard(String),
+
     #[error("Couldn't parse CLI argument: '{0}'")]
     ParseCliArgument(String),
 
@@ -87,3 +194,12 @@ pub enum Error {
     #[error("There is no prev sibling for task with id: '{0}'")]
     NoPrevSibling(TaskId),
 }
+
+/// Cycle of tasks, each one depending on the next, as `1.2 -> 3.1 -> 1.2`
+fn cycle_str(cycle: &[TaskId]) -> String {
+    cycle
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+
+use crate::{prelude::{Project, TaskId, TaskTimes, Error}, task::schedule::SLACK_EPSILON};
+
+/// Downstream leaf whose projected dates were computed again after a slip
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImpactedTask {
+    pub id: TaskId,
+    pub old_finish: NaiveDate,
+    pub new_finish: NaiveDate,
+    /// How much later the task finishes, in days
+    pub delay: f64,
+    /// Part of the slip taken up by slack before reaching the task, in days
+    pub absorbed: f64,
+}
+
+impl ImpactedTask {
+    pub fn is_delayed(&self) -> bool {
+        self.delay > SLACK_EPSILON
+    }
+}
+
+/// What moves when a task finishes later than planned
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlipImpact {
+    /// Slip in days
+    pub slip: f64,
+    /// Slipped leaves and every leaf downstream of them, in dependency order
+    pub tasks: Vec<ImpactedTask>,
+    /// Downstream milestones that are reached later
+    pub milestones: Vec<TaskId>,
+    /// Indices of the sprints with a delayed task in their backlog
+    pub sprints: Vec<usize>,
+    /// Members assigned to a delayed task, sorted by name
+    pub assignees: Vec<String>,
+    pub old_finish: NaiveDate,
+    pub new_finish: NaiveDate,
+    /// How much later the whole project finishes, in days
+    pub project_delay: f64,
+}
+
+impl Project {
+
+    /// Reschedule the remaining work as if `task_id` took `slip` more days, with work starting
+    /// on `start`. A trunk slips through the leaves that finish last in its subtree. Only the
+    /// tasks downstream of the slip, following `dependency_for` edges, are reported. Finish
+    /// dates fall on working days of the project calendar.
+    pub fn slip_impact(&self, task_id: &TaskId, slip: f64, start: NaiveDate) -> Result<SlipImpact, Error> {
+        let tasks = &self.tasks;
+        tasks.get(task_id)?;
+        let order = tasks.topological_order();
+        let durations = order
+            .iter()
+            .map(|id| (*id, tasks.get(id).unwrap().remaining_duration()))
+            .collect::<HashMap<_, _>>();
+        let before = tasks.schedule_passes(&order, &durations);
+
+        let leaves = tasks.leaves_under(task_id);
+        let last_finish = leaves.iter().map(|id| before[id].earliest_finish).fold(0.0, f64::max);
+        let slipped = leaves
+            .into_iter()
+            .filter(|id| (before[id].earliest_finish - last_finish).abs() <= SLACK_EPSILON)
+            .collect::<HashSet<_>>();
+        let mut slipped_durations = durations.clone();
+        slipped.iter().for_each(|id| *slipped_durations.get_mut(id).unwrap() += slip);
+        let after = tasks.schedule_passes(&order, &slipped_durations);
+
+        // everything reachable downstream of the slipped leaves
+        let mut downstream = slipped.clone();
+        let mut stack = slipped.iter().cloned().collect::<Vec<_>>();
+        while let Some(id) = stack.pop() {
+            for (dependent_id, _) in tasks.leaf_dependents(id) {
+                if downstream.insert(dependent_id) {
+                    stack.push(dependent_id);
+                }
+            }
+        }
+
+        let date = |days: f64| self.calendar.finish_date(start, days.ceil() as u32);
+        let impacted = order
+            .iter()
+            .filter(|id| downstream.contains(*id))
+            .map(|id| {
+                let delay = after[id].earliest_finish - before[id].earliest_finish;
+                ImpactedTask {
+                    id: (*id).clone(),
+                    old_finish: date(before[id].earliest_finish),
+                    new_finish: date(after[id].earliest_finish),
+                    delay,
+                    absorbed: (slip - delay).max(0.0),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let delayed = impacted
+            .iter()
+            .filter(|task| task.is_delayed())
+            .map(|task| &task.id)
+            .collect::<HashSet<_>>();
+        let milestones = impacted
+            .iter()
+            .filter(|task| task.is_delayed() && tasks.get(&task.id).unwrap().is_milestone())
+            .map(|task| task.id.clone())
+            .collect();
+        let mut assignees = delayed
+            .iter()
+            .flat_map(|id| self.members.assignees(id))
+            .map(|member| member.name().to_string())
+            .collect::<Vec<_>>();
+        assignees.sort();
+        assignees.dedup();
+
+        let finish = |times: &HashMap<&TaskId, TaskTimes>| times.values().map(|t| t.earliest_finish).fold(0.0, f64::max);
+        Ok(SlipImpact {
+            slip,
+            milestones,
+            sprints: self.sprints.sprints_containing(&delayed),
+            assignees,
+            old_finish: date(finish(&before)),
+            new_finish: date(finish(&after)),
+            project_delay: finish(&after) - finish(&before),
+            tasks: impacted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Estimate;
+
+    #[test]
+    fn slip_impact() {
+        let mut project = Project::new("Project");
+        let start = NaiveDate::from_ymd(2022, 10, 3);
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Design"),
+                ("", "Backend"),
+                ("", "Frontend"),
+                ("", "Docs"),
+            ])?;
+            tasks.add_milestone(TaskId::get_root_id(), "Release", NaiveDate::from_ymd(2022, 10, 14))?;
+            for (id, hours) in [("1", 16.0), ("2", 40.0), ("3", 24.0), ("4", 8.0)] {
+                tasks.estimate(&TaskId::parse(id)?, Some(Estimate::new(hours)?))?;
+            }
+            tasks.add_dependency(&TaskId::parse("2")?, &TaskId::parse("1")?)?;
+            tasks.add_dependency(&TaskId::parse("3")?, &TaskId::parse("1")?)?;
+            tasks.add_dependency(&TaskId::parse("5")?, &TaskId::parse("2")?)?;
+            tasks.add_dependency(&TaskId::parse("5")?, &TaskId::parse("3")?)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.assign_task_to_member(TaskId::parse("2")?, "alice")?;
+            members.assign_task_to_member(TaskId::parse("3")?, "bob")?;
+            Ok(())
+        }).unwrap();
+
+        // frontend has 2 days of slack, so only 1 of the 3 days reaches the release
+        project.add_holiday(NaiveDate::from_ymd(2022, 10, 12)).unwrap();
+        let impact = project.slip_impact(&TaskId::parse("3").unwrap(), 3.0, start).unwrap();
+        let ids = impact.tasks.iter().map(|task| task.id.to_string()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["3", "5"]);
+        assert_eq!(impact.tasks[1].delay, 1.0);
+        assert_eq!(impact.tasks[1].absorbed, 2.0);
+        assert_eq!(impact.milestones, vec![TaskId::parse("5").unwrap()]);
+        assert_eq!(impact.assignees, vec!["bob".to_string()]);
+        assert_eq!(impact.project_delay, 1.0);
+        // finish dates skip the weekend, and the holiday the release slips into
+        assert_eq!((impact.tasks[0].old_finish, impact.tasks[0].new_finish), (NaiveDate::from_ymd(2022, 10, 7), NaiveDate::from_ymd(2022, 10, 13)));
+        assert_eq!((impact.old_finish, impact.new_finish), (NaiveDate::from_ymd(2022, 10, 11), NaiveDate::from_ymd(2022, 10, 13)));
+
+        // design delays everything but the docs
+        let impact = project.slip_impact(&TaskId::parse("1").unwrap(), 1.0, start).unwrap();
+        assert_eq!(impact.tasks.len(), 4);
+        assert_eq!(impact.assignees, vec!["alice".to_string(), "bob".to_string()]);
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::NaiveDate;
 
-use crate::{task::task_id::TaskId, prelude::{Project, Member, Error}};
+use crate::{task::task_id::TaskId, prelude::{Project, Member, Team, Availability, RoutineException, Proficiency, Error}};
 
 #[derive(Debug)]
 pub struct MemberExecution<'a> {
@@ -28,21 +28,103 @@ impl<'a> MemberExecution<'a> {
         Ok(self)
     }
 
+    /// Rename member `old` to `new` everywhere the name is used: assignments and work logs
+    pub fn rename_member(&mut self, old: &str, new: &str) -> Result<&mut Self, Error> {
+        self.project.members.rename(old, new)?;
+        self.project.tasks.rename_member(old, new);
+        Ok(self)
+    }
+
     pub fn remove_member(&mut self, name: &str) -> Result<Member, Error> {
         self.project.members.remove_member(name, &mut self.project.tasks)
     }
 
-    pub fn add_routine_exception(&mut self, name: &str, date: &NaiveDate, cost: f64) -> Result<&mut Self, Error> {
+    pub fn add_role(&mut self, name: &str, role: &str) -> Result<&mut Self, Error> {
+        self.project.members.get_mut(name)?.add_role(role);
+        Ok(self)
+    }
+
+    pub fn remove_role(&mut self, name: &str, role: &str) -> Result<&mut Self, Error> {
+        self.project.members.get_mut(name)?.remove_role(role)?;
+        Ok(self)
+    }
+
+    /// Add a skill to member `name`, or change the level of one they have
+    pub fn set_skill(&mut self, name: &str, skill: &str, level: Proficiency) -> Result<&mut Self, Error> {
+        self.project.members.get_mut(name)?.set_skill(skill, level);
+        Ok(self)
+    }
+
+    pub fn remove_skill(&mut self, name: &str, skill: &str) -> Result<&mut Self, Error> {
+        self.project.members.get_mut(name)?.remove_skill(skill)?;
+        Ok(self)
+    }
+
+    /// Change the hourly rate of member `name` from `since` on
+    pub fn set_rate(&mut self, name: &str, since: NaiveDate, hourly: f64) -> Result<&mut Self, Error> {
+        self.project.members.get_mut(name)?.set_rate(since, hourly)?;
+        Ok(self)
+    }
+
+    pub fn remove_rate(&mut self, name: &str, since: &NaiveDate) -> Result<&mut Self, Error> {
+        self.project.members.get_mut(name)?.remove_rate(since)?;
+        Ok(self)
+    }
+
+    /// Change the weekly availability of member `name` from a list like `mon-thu=8,fri=4`
+    pub fn update_availability(&mut self, name: &str, spec: &str) -> Result<&mut Self, Error> {
+        self.project.members
+            .get_mut(name)?
+            .availability_mut()
+            .update(spec)?;
+        Ok(self)
+    }
+
+    pub fn set_availability(&mut self, name: &str, availability: Availability) -> Result<&mut Self, Error> {
+        *self.project.members
+            .get_mut(name)?
+            .availability_mut() = availability;
+        Ok(self)
+    }
+
+    /// Set the hours member `name` is available on a single day
+    pub fn add_routine_exception(&mut self, name: &str, date: &NaiveDate, hours: f64) -> Result<&mut Self, Error> {
+        self.add_exception(name, RoutineException::range(*date, *date, hours))
+    }
+
+    /// Add a date range or recurring exception. It wins over the existing ones on the days
+    /// they share.
+    pub fn add_exception(&mut self, name: &str, exception: RoutineException) -> Result<&mut Self, Error> {
         self.project.members
             .get_mut(name)?
-            .add_routine_exception(date, cost);
+            .add_routine_exception(exception)?;
         Ok(self)
     }
 
+    /// Replace exception number `index` of member `name`, as listed by
+    /// `Member::routine_exceptions`
+    pub fn edit_exception(&mut self, name: &str, index: usize, exception: RoutineException) -> Result<&mut Self, Error> {
+        self.project.members
+            .get_mut(name)?
+            .replace_routine_exception(index, exception)?;
+        Ok(self)
+    }
+
+    pub fn remove_exception(&mut self, name: &str, index: usize) -> Result<RoutineException, Error> {
+        self.project.members
+            .get_mut(name)?
+            .remove_routine_exception(index)
+    }
+
     pub fn remove_routine_exception(&mut self, name: &str, date: &NaiveDate) -> Result<&mut Self, Error> {
+        self.remove_routine_exceptions_between(name, date, date)
+    }
+
+    /// Clear the days from `from` to `to` of the exceptions of member `name`
+    pub fn remove_routine_exceptions_between(&mut self, name: &str, from: &NaiveDate, to: &NaiveDate) -> Result<&mut Self, Error> {
         self.project.members
             .get_mut(name)?
-            .remove_routine_exception(date);
+            .remove_routine_exceptions_between(from, to);
         Ok(self)
     }
 
@@ -51,6 +133,44 @@ impl<'a> MemberExecution<'a> {
         Ok(self)
     }
 
+    /// Assign member `name` to do `allocation` percent of task `id`, or change their allocation
+    /// if they already are
+    pub fn assign_with_allocation(&mut self, id: TaskId, name: &str, allocation: u32) -> Result<&mut Self, Error> {
+        self.project.members.assign_with_allocation(id, name, allocation, &mut self.project.tasks)?;
+        Ok(self)
+    }
+
+    pub fn add_team(&mut self, name: &str) -> Result<&mut Self, Error> {
+        self.project.members.add_team(name)?;
+        Ok(self)
+    }
+
+    /// Remove team `name`, leaving the tasks assigned to it without a team
+    pub fn remove_team(&mut self, name: &str) -> Result<Team, Error> {
+        self.project.members.remove_team(name)
+    }
+
+    pub fn join_team(&mut self, team: &str, name: &str) -> Result<&mut Self, Error> {
+        self.project.members.join_team(team, name)?;
+        Ok(self)
+    }
+
+    pub fn leave_team(&mut self, team: &str, name: &str) -> Result<&mut Self, Error> {
+        self.project.members.leave_team(team, name)?;
+        Ok(self)
+    }
+
+    /// Assign leaf `id` to team `team` as a whole, in place of the team it was assigned to
+    pub fn assign_team(&mut self, id: TaskId, team: &str) -> Result<&mut Self, Error> {
+        self.project.members.assign_team(id, team, &self.project.tasks)?;
+        Ok(self)
+    }
+
+    pub fn unassign_team(&mut self, id: &TaskId) -> Result<&mut Self, Error> {
+        self.project.members.unassign_team(id)?;
+        Ok(self)
+    }
+
     pub fn remove_member_from_task(&mut self, id: TaskId, name: &str) -> Result<&mut Self, Error> {
         self.project.members.remove_member_from_task(&id, name, &mut self.project.tasks)?;
         Ok(self)
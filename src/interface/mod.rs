@@ -1,3 +1,2 @@
 pub mod member_execution;
 pub mod task_execution;
-pub mod sprint_execution;
@@ -1,5 +0,0 @@
-use crate::prelude::Project;
-
-pub struct SprintExecution<'a> {
-    project: &'a mut Project
-}
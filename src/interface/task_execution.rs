@@ -1,4 +1,6 @@
-use crate::{task::task_id::TaskId, project::Project, error::Error};
+use chrono::NaiveDate;
+
+use crate::{member::skill::Proficiency, task::{task_id::TaskId, estimate::Estimate, dependency::DependencyLink, work_log::WorkLog, SplitAmounts}, project::Project, error::Error};
 
 #[derive(Debug)]
 pub struct TaskExecution<'a> {
@@ -7,14 +9,35 @@ pub struct TaskExecution<'a> {
 
 impl<'a> TaskExecution<'a> {
 
-    pub(crate) fn new(project: &'a mut Project) -> TaskExecution {
+    pub(crate) fn new(project: &'a mut Project) -> TaskExecution<'a> {
         Self {
             project
         }
     }
 
+    /// Add a task under `id`. If `id` was a leaf, its estimate, costs, logged hours and
+    /// assignments go to the new task.
     pub fn add(&mut self, id: TaskId, name: &str) -> Result<&mut Self, Error> {
-        self.project.tasks.add_task(id, name)?;
+        let child_id = self.project.tasks.add_task(id.clone(), name)?.id().clone();
+        self.hand_down_assignments(&id, &child_id)
+    }
+
+    pub fn add_milestone(&mut self, id: TaskId, name: &str, target_date: NaiveDate) -> Result<&mut Self, Error> {
+        let child_id = self.project.tasks.add_milestone(id.clone(), name, target_date)?.id().clone();
+        self.hand_down_assignments(&id, &child_id)
+    }
+
+    /// Members and team of `parent` follow the work to `child` when it is its first child
+    fn hand_down_assignments(&mut self, parent: &TaskId, child: &TaskId) -> Result<&mut Self, Error> {
+        if self.project.tasks.get(parent)?.num_child == 1 {
+            self.project.members.move_assignments(parent, child);
+            self.project.members.move_team_assignment(parent, child);
+        }
+        Ok(self)
+    }
+
+    pub fn target_date(&mut self, id: &TaskId, target_date: NaiveDate) -> Result<&mut Self, Error> {
+        self.project.tasks.set_target_date(id, target_date)?;
         Ok(self)
     }
 
@@ -23,11 +46,44 @@ impl<'a> TaskExecution<'a> {
         Ok(self)
     }
 
+    pub fn add_dependency_with(&mut self, id: &TaskId, dependency: &TaskId, link: DependencyLink) -> Result<&mut Self, Error> {
+        self.project.tasks.add_dependency_with(id, dependency, link)?;
+        Ok(self)
+    }
+
+    pub fn dependency_link(&mut self, id: &TaskId, dependency: &TaskId, link: DependencyLink) -> Result<&mut Self, Error> {
+        self.project.tasks.set_dependency_link(id, dependency, link)?;
+        Ok(self)
+    }
+
     pub fn remove_dependency(&mut self, id: &TaskId, dependency: &TaskId) -> Result<&mut Self, Error> {
         self.project.tasks.remove_dependency(id, dependency)?;
         Ok(self)
     }
 
+    /// Remove the dependencies implied by other ones, returning them as (task, dependency)
+    pub fn reduce_dependencies(&mut self) -> Result<Vec<(TaskId, TaskId)>, Error> {
+        self.project.tasks.reduce_dependencies()
+    }
+
+    /// Level resources from `start` on and store the planned dates of the leaves that aren't done.
+    /// Leaves left out of the plan, like the done ones, lose the dates they had.
+    pub fn level(&mut self, start: NaiveDate) -> Result<&mut Self, Error> {
+        let plan = self.project.plan_schedule(start)?;
+        let stale = self.project.tasks
+            .get_tasks()
+            .filter(|task| task.planned_dates().is_some() && !plan.iter().any(|planned| planned.id == *task.id()))
+            .map(|task| task.id().clone())
+            .collect::<Vec<_>>();
+        for id in stale {
+            self.project.tasks.set_planned_dates(&id, None)?;
+        }
+        for planned in plan {
+            self.project.tasks.set_planned_dates(&planned.id, Some((planned.start, planned.finish)))?;
+        }
+        Ok(self)
+    }
+
     pub fn remove(&mut self, id: &TaskId) -> Result<&mut Self, Error> {
         self.project.tasks.remove(id, &self.project.members)?;
         Ok(self)
@@ -38,13 +94,117 @@ impl<'a> TaskExecution<'a> {
         Ok(self)
     }
 
+    /// Split leaf `id` into subtasks, spreading its planned value and actual cost across them.
+    /// Dependencies, member and team assignments are moved to subtask number `target`.
+    pub fn split(&mut self, id: &TaskId, names: &[&str], planned_values: &SplitAmounts, actual_costs: &SplitAmounts, target: u32) -> Result<&mut Self, Error> {
+        let target_id = self.project.tasks.split_task(id, names, planned_values, actual_costs, target)?;
+        self.project.members.move_assignments(id, &target_id);
+        self.project.members.move_team_assignment(id, &target_id);
+        Ok(self)
+    }
+
+    /// Mark leaf `id` as done with the cost it took, which is kept over the one of the
+    /// logged hours
     pub fn done(&mut self, id: &TaskId, cost: f64) -> Result<&mut Self, Error> {
         self.project.tasks.set_actual_cost(id, cost)?;
+        self.project.tasks.get_mut(id)?.manual_actual_cost = Some(true);
+        Ok(self)
+    }
+
+    /// Mark leaf `id` as done with the cost of its logged hours
+    pub fn done_from_logs(&mut self, id: &TaskId) -> Result<&mut Self, Error> {
+        let cost = self.project.logged_cost(id)?;
+        self.project.tasks.set_actual_cost(id, cost)?;
+        self.project.tasks.get_mut(id)?.manual_actual_cost = Some(false);
+        Ok(self)
+    }
+
+    /// Log `hours` of work by member `name` on leaf `id`. Unless it was set by hand, the actual
+    /// cost of the leaf follows.
+    pub fn log_work(&mut self, id: &TaskId, name: &str, date: NaiveDate, hours: f64) -> Result<&mut Self, Error> {
+        if self.project.members.get(name)?.rate_on(&date).is_none() {
+            return Err(Error::NoRate(name.to_string(), date));
+        }
+        self.project.tasks.add_work_log(id, WorkLog { member: name.to_string(), date, hours })?;
+        if !self.project.tasks.get(id)?.has_manual_actual_cost() {
+            let cost = self.project.logged_cost(id)?;
+            self.project.tasks.update_actual_cost(id, cost)?;
+        }
+        Ok(self)
+    }
+
+    /// Derive the planned value of the leaves from their estimate and the rates of their
+    /// assignees, and their actual cost from their logged hours, leaving alone the values
+    /// set by hand and the leaves missing what their value is derived from, like a rate
+    pub fn derive_costs(&mut self, as_of: NaiveDate) -> Result<&mut Self, Error> {
+        let ids = self.project.tasks
+            .get_tasks()
+            .filter(|task| !task.is_milestone())
+            .map(|task| task.id().clone())
+            .collect::<Vec<_>>();
+        for id in ids {
+            let task = self.project.tasks.get(&id)?;
+            let (manual_planned_value, manual_actual_cost, logged) = (task.has_manual_planned_value(), task.has_manual_actual_cost(), !task.work_logs.is_empty());
+            if !manual_planned_value {
+                if let Some(planned_value) = Self::unless_missing(self.project.estimated_cost(&id, as_of))?.flatten() {
+                    self.project.tasks.set_planned_value(&id, planned_value)?;
+                }
+            }
+            if !manual_actual_cost && logged {
+                if let Some(cost) = Self::unless_missing(self.project.logged_cost(&id))? {
+                    self.project.tasks.update_actual_cost(&id, cost)?;
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// None when a value can't be derived because a rate or a member is missing
+    fn unless_missing<T>(value: Result<T, Error>) -> Result<Option<T>, Error> {
+        match value {
+            Ok(value) => Ok(Some(value)),
+            Err(Error::NoRate(..) | Error::MemberNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Let the planned value and actual cost of leaf `id` be derived again
+    pub fn reset_cost_overrides(&mut self, id: &TaskId) -> Result<&mut Self, Error> {
+        let task = self.project.tasks.get_mut(id)?;
+        task.manual_planned_value = Some(false);
+        task.manual_actual_cost = Some(false);
+        Ok(self)
+    }
+
+    pub fn priority(&mut self, id: &TaskId, priority: i32) -> Result<&mut Self, Error> {
+        self.project.tasks.set_priority(id, priority)?;
+        Ok(self)
+    }
+
+    pub fn due_date(&mut self, id: &TaskId, due_date: Option<NaiveDate>) -> Result<&mut Self, Error> {
+        self.project.tasks.set_due_date(id, due_date)?;
+        Ok(self)
+    }
+
+    pub fn estimate(&mut self, id: &TaskId, estimate: Option<Estimate>) -> Result<&mut Self, Error> {
+        self.project.tasks.set_estimate(id, estimate)?;
         Ok(self)
     }
 
     pub fn planned_value(&mut self, id: &TaskId, planned_value: f64) -> Result<&mut Self, Error> {
         self.project.tasks.set_planned_value(id, planned_value)?;
+        self.project.tasks.get_mut(id)?.manual_planned_value = Some(true);
+        Ok(self)
+    }
+
+    /// Require skill `skill` at `level` or above from the assignees of leaf `id`
+    pub fn require_skill(&mut self, id: &TaskId, skill: &str, level: Proficiency) -> Result<&mut Self, Error> {
+        self.project.tasks.require_skill(id, skill, level)?;
+        Ok(self)
+    }
+
+    pub fn remove_required_skill(&mut self, id: &TaskId, skill: &str) -> Result<&mut Self, Error> {
+        self.project.tasks.remove_required_skill(id, skill)?;
         Ok(self)
     }
 }
@@ -4,5 +4,15 @@ mod sprint;
 mod project;
 mod interface;
 mod error;
+mod validation;
+mod impact;
+mod blocking;
+mod calendar;
+mod scheduler;
+mod suggestion;
+mod costing;
+mod workload;
+mod team_metrics;
+mod dashboard;
 pub mod prelude;
 pub mod util;
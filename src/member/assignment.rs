@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use crate::task::task_id::TaskId;
+
+/// Allocation of a member who does a whole task, in percent
+pub const FULL_ALLOCATION: u32 = 100;
+
+pub(crate) fn serialize_assignments<S: Serializer>(assignments: &HashMap<TaskId, u32>, serializer: S) -> Result<S::Ok, S::Error> {
+    assignments.iter().collect::<Vec<_>>().serialize(serializer)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Assignments {
+    Allocated(Vec<(TaskId, u32)>),
+    /// Task ids alone, from before assignments had an allocation
+    Full(Vec<TaskId>),
+}
+
+pub(crate) fn deserialize_assignments<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<TaskId, u32>, D::Error> {
+    Ok(match Assignments::deserialize(deserializer)? {
+        Assignments::Allocated(assignments) => assignments.into_iter().collect(),
+        Assignments::Full(ids) => ids.into_iter().map(|id| (id, FULL_ALLOCATION)).collect(),
+    })
+}
@@ -0,0 +1,109 @@
+use std::fmt::Display;
+
+use chrono::{Weekday, Datelike, NaiveDate};
+use serde::{Serialize, Deserialize};
+
+use crate::{error::Error, task::estimate::HOURS_PER_DAY};
+
+const WEEKDAYS: [Weekday; 7] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
+/// Working hours of a member on each day of the week
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Availability {
+    /// Monday first
+    hours: [f64; 7],
+}
+
+impl Default for Availability {
+    /// A full working day from Monday to Friday
+    fn default() -> Self {
+        let mut hours = [0.0; 7];
+        hours[..5].fill(HOURS_PER_DAY);
+        Self { hours }
+    }
+}
+
+impl Availability {
+
+    pub fn new(hours: [f64; 7]) -> Result<Self, Error> {
+        let mut availability = Self { hours: [0.0; 7] };
+        WEEKDAYS
+            .iter()
+            .zip(hours)
+            .try_for_each(|(weekday, hours)| availability.set_hours(*weekday, hours))?;
+        Ok(availability)
+    }
+
+    pub fn hours(&self, weekday: Weekday) -> f64 {
+        self.hours[weekday.num_days_from_monday() as usize]
+    }
+
+    pub fn hours_on(&self, date: &NaiveDate) -> f64 {
+        self.hours(date.weekday())
+    }
+
+    pub fn weekly_hours(&self) -> f64 {
+        self.hours.iter().sum()
+    }
+
+    pub fn set_hours(&mut self, weekday: Weekday, hours: f64) -> Result<(), Error> {
+        if !(0.0..=24.0).contains(&hours) {
+            return Err(Error::BadAvailability(format!("{} {}h", weekday, hours)))
+        }
+        self.hours[weekday.num_days_from_monday() as usize] = hours;
+        Ok(())
+    }
+
+    /// Change the hours of some days from a list like `mon-thu=8,fri=4`, leaving the other
+    /// days untouched. Nothing changes if any entry is invalid.
+    pub fn update(&mut self, spec: &str) -> Result<(), Error> {
+        let bad = || Error::BadAvailability(spec.to_string());
+        let mut updated = *self;
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (days, hours) = entry.split_once('=').ok_or_else(bad)?;
+            let hours = hours.trim().trim_end_matches('h').parse::<f64>().map_err(|_| bad())?;
+            let (first, last) = days.split_once('-').unwrap_or((days, days));
+            let first = first.trim().parse::<Weekday>().map_err(|_| bad())?.num_days_from_monday() as usize;
+            let last = last.trim().parse::<Weekday>().map_err(|_| bad())?.num_days_from_monday() as usize;
+            if first > last {
+                return Err(bad())
+            }
+            WEEKDAYS[first..=last]
+                .iter()
+                .try_for_each(|weekday| updated.set_hours(*weekday, hours))?;
+        }
+        *self = updated;
+        Ok(())
+    }
+}
+
+impl Display for Availability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = WEEKDAYS
+            .iter()
+            .map(|weekday| format!("{} {}h", weekday, self.hours(*weekday)))
+            .collect::<Vec<_>>();
+        write!(f, "{}", days.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update() {
+        let mut availability = Availability::default();
+        assert_eq!(availability.weekly_hours(), 40.0);
+        availability.update("mon-thu=8, fri=4h").unwrap();
+        assert_eq!(availability.hours(Weekday::Fri), 4.0);
+        assert_eq!(availability.hours_on(&NaiveDate::from_ymd(2022, 10, 7)), 4.0);
+        assert_eq!(availability.weekly_hours(), 36.0);
+        assert!(availability.update("sat=2,fri-mon=8").is_err());
+        assert!(availability.update("sun=25").is_err());
+        assert!(availability.update("someday=8").is_err());
+        assert_eq!(availability.weekly_hours(), 36.0);
+        assert_eq!(availability.to_string(), "Mon 8h, Tue 8h, Wed 8h, Thu 8h, Fri 4h, Sat 0h, Sun 0h");
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{NaiveDate, Duration};
+
+use crate::prelude::{Member, Members, Calendar, Error};
+
+use super::exception::RoutineException;
+
+/// Hours a member can work on a given day
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayCapacity<'a> {
+    pub date: NaiveDate,
+    pub hours: f64,
+    /// Exception that replaced the weekly availability on that day, if any
+    pub exception: Option<&'a RoutineException>,
+}
+
+impl Member {
+
+    /// Hours available on each day from `from` to `to`, both included. Holidays of `calendar`
+    /// are off, unless a routine exception says otherwise.
+    pub fn capacity(&self, from: &NaiveDate, to: &NaiveDate, calendar: &Calendar) -> Result<Vec<DayCapacity<'_>>, Error> {
+        if from > to {
+            return Err(Error::BadDateRange(*from, *to))
+        }
+        let days = (*to - *from).num_days();
+        Ok((0..=days)
+            .map(|offset| {
+                let date = *from + Duration::days(offset);
+                let exception = self.get_routine_exception(&date);
+                let hours = match exception {
+                    Some(exception) => exception.hours,
+                    None if calendar.is_holiday(&date) => 0.0,
+                    None => self.availability().hours_on(&date),
+                };
+                DayCapacity { date, hours, exception }
+            })
+            .collect())
+    }
+
+    /// Total hours available from `from` to `to`, both included
+    pub fn total_capacity(&self, from: &NaiveDate, to: &NaiveDate, calendar: &Calendar) -> Result<f64, Error> {
+        Ok(self.capacity(from, to, calendar)?.iter().map(|day| day.hours).sum())
+    }
+}
+
+impl Members {
+
+    /// Capacity of every member from `from` to `to`, sorted by name
+    pub fn capacity(&self, from: &NaiveDate, to: &NaiveDate, calendar: &Calendar) -> Result<Vec<(&Member, Vec<DayCapacity<'_>>)>, Error> {
+        let mut members = self.members().collect::<Vec<_>>();
+        members.sort_by_key(|member| member.name());
+        members
+            .into_iter()
+            .map(|member| Ok((member, member.capacity(from, to, calendar)?)))
+            .collect()
+    }
+
+    /// Capacity of the members of team `team` from `from` to `to`, sorted by name
+    pub fn team_capacity(&self, team: &str, from: &NaiveDate, to: &NaiveDate, calendar: &Calendar) -> Result<Vec<(&Member, Vec<DayCapacity<'_>>)>, Error> {
+        self.team(team)?
+            .members()
+            .map(|name| {
+                let member = self.get(name)?;
+                Ok((member, member.capacity(from, to, calendar)?))
+            })
+            .collect()
+    }
+
+    /// Hours the members of team `team` can work from `from` to `to`, all together
+    pub fn total_team_capacity(&self, team: &str, from: &NaiveDate, to: &NaiveDate, calendar: &Calendar) -> Result<f64, Error> {
+        Ok(self.team_capacity(team, from, to, calendar)?
+            .iter()
+            .flat_map(|(_, days)| days.iter().map(|day| day.hours))
+            .fold(0.0, |total, hours| total + hours))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capacity() {
+        let mut members = Members::new();
+        members.insert("alice".to_string()).unwrap();
+        let alice = members.get_mut("alice").unwrap();
+        alice.availability_mut().update("fri=4").unwrap();
+        alice.add_routine_exception(RoutineException::weekly(NaiveDate::from_ymd(2022, 10, 3), 2, 0.0)).unwrap();
+        alice.add_routine_exception(RoutineException::range(NaiveDate::from_ymd(2022, 10, 6), NaiveDate::from_ymd(2022, 10, 6), 6.0)).unwrap();
+        // Wednesday and Thursday are holidays, but alice works a few hours on Thursday anyway
+        let mut calendar = Calendar::default();
+        calendar.add_holiday(NaiveDate::from_ymd(2022, 10, 5));
+        calendar.add_holiday(NaiveDate::from_ymd(2022, 10, 6));
+
+        // Monday to Sunday, the first Monday being off but not the next one
+        let from = NaiveDate::from_ymd(2022, 10, 3);
+        let to = NaiveDate::from_ymd(2022, 10, 9);
+        let capacity = members.capacity(&from, &to, &calendar).unwrap();
+        let (member, days) = &capacity[0];
+        assert_eq!(member.name(), "alice");
+        assert_eq!(days.iter().map(|day| day.hours).collect::<Vec<_>>(), vec![0.0, 8.0, 0.0, 6.0, 4.0, 0.0, 0.0]);
+        assert!(days[0].exception.is_some() && days[1].exception.is_none() && days[2].exception.is_none());
+        assert_eq!(member.total_capacity(&from, &(to + Duration::days(7)), &calendar), Ok(54.0));
+        assert_eq!(member.total_capacity(&from, &(to + Duration::days(7)), &Calendar::default()), Ok(62.0));
+        assert_eq!(member.capacity(&to, &from, &calendar), Err(Error::BadDateRange(to, from)));
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use chrono::{NaiveDate, Datelike, Duration, NaiveDateTime};
+use serde::{Serialize, Deserialize, Deserializer};
+
+use crate::error::Error;
+
+/// How a routine exception repeats
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recurrence {
+    /// Every day from the start to the end
+    Once,
+    /// Every `interval` weeks, on the weekday of the start
+    Weekly { interval: u32 },
+    /// On the `week`-th weekday of the start in each month, -1 being the last one
+    Monthly { week: i8 },
+}
+
+/// Change to the usual availability of a member, on one or more days
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoutineException {
+    pub start: NaiveDate,
+    /// Last day the exception applies. One-off exceptions without one only last a day,
+    /// recurring ones without one never stop.
+    pub end: Option<NaiveDate>,
+    pub recurrence: Recurrence,
+    /// Hours available on each day the exception applies
+    pub hours: f64,
+    pub reason: Option<String>,
+}
+
+impl RoutineException {
+
+    /// Exception for every day from `start` to `end`, both included
+    pub fn range(start: NaiveDate, end: NaiveDate, hours: f64) -> Self {
+        Self { start, end: Some(end), recurrence: Recurrence::Once, hours, reason: None }
+    }
+
+    /// Exception every `interval` weeks, on the weekday of `start`
+    pub fn weekly(start: NaiveDate, interval: u32, hours: f64) -> Self {
+        Self { start, end: None, recurrence: Recurrence::Weekly { interval }, hours, reason: None }
+    }
+
+    /// Exception on the `week`-th weekday of `start` in each month, -1 being the last one
+    pub fn monthly(start: NaiveDate, week: i8, hours: f64) -> Self {
+        Self { start, end: None, recurrence: Recurrence::Monthly { week }, hours, reason: None }
+    }
+
+    pub fn until(mut self, end: NaiveDate) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+
+    /// Last day the exception can apply, `None` if it repeats forever
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        match self.recurrence {
+            Recurrence::Once => Some(self.end.unwrap_or(self.start)),
+            _ => self.end,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let valid = (0.0..=24.0).contains(&self.hours)
+            && self.last_day().is_none_or(|end| end >= self.start)
+            && match self.recurrence {
+                Recurrence::Once => true,
+                Recurrence::Weekly { interval } => interval > 0,
+                Recurrence::Monthly { week } => (1..=5).contains(&week) || week == -1,
+            };
+        if !valid {
+            return Err(Error::BadRoutineException(self.to_string()))
+        }
+        Ok(())
+    }
+
+    pub fn applies_on(&self, date: &NaiveDate) -> bool {
+        if *date < self.start || self.last_day().is_some_and(|end| *date > end) {
+            return false
+        }
+        match self.recurrence {
+            Recurrence::Once => true,
+            Recurrence::Weekly { interval } => date.weekday() == self.start.weekday()
+                && ((*date - self.start).num_days() / 7) % interval as i64 == 0,
+            Recurrence::Monthly { week } => date.weekday() == self.start.weekday()
+                && if week == -1 {
+                    (*date + Duration::days(7)).month() != date.month()
+                } else {
+                    (date.day() as i8 - 1) / 7 + 1 == week
+                },
+        }
+    }
+}
+
+impl Display for RoutineException {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.recurrence {
+            Recurrence::Once => match self.last_day() {
+                Some(end) if end != self.start => write!(f, "{} to {}", self.start, end)?,
+                _ => write!(f, "{}", self.start)?,
+            },
+            Recurrence::Weekly { interval } => {
+                match interval {
+                    1 => write!(f, "every {}", self.start.weekday())?,
+                    2 => write!(f, "every other {}", self.start.weekday())?,
+                    interval => write!(f, "every {} weeks on {}", interval, self.start.weekday())?,
+                }
+                write!(f, " from {}", self.start)?;
+            },
+            Recurrence::Monthly { week } => {
+                let nth = match week {
+                    1 => "first",
+                    2 => "second",
+                    3 => "third",
+                    4 => "fourth",
+                    5 => "fifth",
+                    _ => "last",
+                };
+                write!(f, "{} {} of the month from {}", nth, self.start.weekday(), self.start)?;
+            },
+        }
+        if let (Some(end), false) = (self.end, self.recurrence == Recurrence::Once) {
+            write!(f, " until {}", end)?;
+        }
+        write!(f, ": {}h", self.hours)?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({})", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads both the current list of exceptions and the old map of single days to hours
+pub(crate) fn deserialize_exceptions<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<RoutineException>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Rules(Vec<RoutineException>),
+        Days(Vec<(NaiveDateTime, f64)>),
+    }
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Rules(rules) => rules,
+        Stored::Days(days) => days
+            .into_iter()
+            .map(|(date, hours)| RoutineException::range(date.date(), date.date(), hours))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recurrence() {
+        // Friday
+        let start = NaiveDate::from_ymd(2022, 10, 7);
+        let every_other_friday = RoutineException::weekly(start, 2, 0.0);
+        assert!(every_other_friday.applies_on(&start));
+        assert!(!every_other_friday.applies_on(&NaiveDate::from_ymd(2022, 10, 14)));
+        assert!(every_other_friday.applies_on(&NaiveDate::from_ymd(2022, 10, 21)));
+        assert!(!every_other_friday.applies_on(&NaiveDate::from_ymd(2022, 9, 23)));
+        assert_eq!(every_other_friday.to_string(), "every other Fri from 2022-10-07: 0h");
+
+        // Monday
+        let first_monday = RoutineException::monthly(NaiveDate::from_ymd(2022, 10, 3), 1, 4.0)
+            .until(NaiveDate::from_ymd(2022, 12, 31))
+            .with_reason("planning");
+        assert!(first_monday.applies_on(&NaiveDate::from_ymd(2022, 11, 7)));
+        assert!(!first_monday.applies_on(&NaiveDate::from_ymd(2022, 11, 14)));
+        assert!(!first_monday.applies_on(&NaiveDate::from_ymd(2023, 1, 2)));
+        assert_eq!(first_monday.to_string(), "first Mon of the month from 2022-10-03 until 2022-12-31: 4h (planning)");
+
+        let last_friday = RoutineException::monthly(start, -1, 0.0);
+        assert!(last_friday.applies_on(&NaiveDate::from_ymd(2022, 10, 28)));
+        assert!(!last_friday.applies_on(&NaiveDate::from_ymd(2022, 10, 21)));
+
+        let vacation = RoutineException::range(NaiveDate::from_ymd(2022, 8, 1), NaiveDate::from_ymd(2022, 8, 12), 0.0);
+        assert!(vacation.applies_on(&NaiveDate::from_ymd(2022, 8, 6)));
+        assert!(!vacation.applies_on(&NaiveDate::from_ymd(2022, 8, 13)));
+        assert!(vacation.validate().is_ok());
+        assert!(RoutineException::range(start, NaiveDate::from_ymd(2022, 10, 1), 0.0).validate().is_err());
+        assert!(RoutineException::monthly(start, 6, 0.0).validate().is_err());
+    }
+}
@@ -1,24 +1,73 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, BTreeMap, BTreeSet};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::serde_as;
 
-use crate::prelude::{Error, Tasks, TaskId};
+use crate::prelude::{Error, Tasks, TaskId, Violation};
 
-use super::Member;
+use super::{Member, assignment::FULL_ALLOCATION, team::Team};
 
 #[serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Members {
     #[serde_as(as="Vec<(_, _)>")]
-    members: HashMap<String, Member>
+    members: HashMap<String, Member>,
+    teams: BTreeMap<String, Team>,
+    /// Team each leaf is assigned to as a whole, if any
+    #[serde_as(as="Vec<(_, _)>")]
+    team_tasks: HashMap<TaskId, String>,
+    /// Names of the members assigned to each task, kept in step with their assignments
+    #[serde(skip)]
+    assignees: HashMap<TaskId, BTreeSet<String>>
+}
+
+impl<'de> Deserialize<'de> for Members {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[serde_as]
+        #[derive(Deserialize)]
+        struct Stored {
+            #[serde_as(as="Vec<(_, _)>")]
+            members: HashMap<String, Member>,
+            #[serde(default)]
+            teams: BTreeMap<String, Team>,
+            #[serde_as(as="Vec<(_, _)>")]
+            #[serde(default)]
+            team_tasks: HashMap<TaskId, String>
+        }
+        let stored = Stored::deserialize(deserializer)?;
+        let mut members = Self { members: stored.members, teams: stored.teams, team_tasks: stored.team_tasks, assignees: HashMap::new() };
+        members.reindex();
+        Ok(members)
+    }
 }
 
 impl Members {
 
     pub(crate) fn new() -> Self {
         Self {
-            members: HashMap::new()
+            members: HashMap::new(),
+            teams: BTreeMap::new(),
+            team_tasks: HashMap::new(),
+            assignees: HashMap::new()
+        }
+    }
+
+    /// Build the task to assignees lookup again from the assignments of the members
+    fn reindex(&mut self) {
+        self.assignees.clear();
+        for member in self.members.values() {
+            for id in member.task_ids() {
+                self.assignees.entry(id.clone()).or_default().insert(member.name().to_string());
+            }
+        }
+    }
+
+    fn unindex(&mut self, id: &TaskId, name: &str) {
+        if let Some(names) = self.assignees.get_mut(id) {
+            names.remove(name);
+            if names.is_empty() {
+                self.assignees.remove(id);
+            }
         }
     }
 
@@ -31,18 +80,160 @@ impl Members {
         self.members.values()
     }
 
+    /// Members assigned to task `id`, sorted by name
+    pub fn assignees<'a>(&'a self, id: &'a TaskId) -> impl Iterator<Item=&'a Member> {
+        self.assignees
+            .get(id)
+            .into_iter()
+            .flatten()
+            .map(|name| &self.members[name])
+    }
+
+    pub fn is_assigned(&self, id: &TaskId) -> bool {
+        self.assignees.contains_key(id)
+    }
+
+    /// Part of task `id` member `name` does: their allocation over the allocations of all the
+    /// assignees, 0 if they aren't assigned to it
+    pub fn share(&self, id: &TaskId, name: &str) -> f64 {
+        let total = self.assignees(id).filter_map(|member| member.allocation(id)).sum::<u32>();
+        match self.members.get(name).and_then(|member| member.allocation(id)) {
+            Some(allocation) if total > 0 => allocation as f64 / total as f64,
+            _ => 0.0
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.members.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
     pub(crate) fn insert(&mut self, name: String) -> Result<(), Error> {
+        if self.members.contains_key(&name) {
+            return Err(Error::MemberAlreadyExists(name))
+        }
         self.members.insert(name.to_string(), Member::new(&name));
         Ok(())
     }
 
+    /// Give member `old` the name `new`, along with their assignments. Fails without changing
+    /// anything if `old` doesn't exist or `new` is taken.
+    pub(crate) fn rename(&mut self, old: &str, new: &str) -> Result<(), Error> {
+        self.get(old)?;
+        if old == new {
+            return Ok(())
+        }
+        if self.members.contains_key(new) {
+            return Err(Error::MemberAlreadyExists(new.to_string()))
+        }
+        for team in self.teams.values_mut() {
+            if team.remove_member(old) {
+                team.add_member(new);
+            }
+        }
+        let mut member = self.members.remove(old).unwrap();
+        member.name = new.to_string();
+        for id in member.task_ids() {
+            let names = self.assignees.get_mut(id).unwrap();
+            names.remove(old);
+            names.insert(new.to_string());
+        }
+        self.members.insert(new.to_string(), member);
+        Ok(())
+    }
+
     pub(crate) fn remove(&mut self, name: &str) -> Result<Member, Error> {
-        self.members.remove(&name[..])
-            .ok_or_else(|| Error::MemberNotFound(name.to_string()))
+        let member = self.members.remove(name)
+            .ok_or_else(|| Error::MemberNotFound(name.to_string()))?;
+        member.task_ids().for_each(|id| self.unindex(id, name));
+        self.teams.values_mut().for_each(|team| { team.remove_member(name); });
+        Ok(member)
+    }
+
+    /// Teams sorted by name
+    pub fn teams(&self) -> impl Iterator<Item=&Team> {
+        self.teams.values()
+    }
+
+    pub fn team(&self, name: &str) -> Result<&Team, Error> {
+        self.teams.get(name)
+            .ok_or_else(|| Error::TeamNotFound(name.to_string()))
+    }
+
+    /// Teams member `name` is in, sorted by name
+    pub fn teams_of<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a Team> {
+        self.teams.values().filter(move |team| team.has_member(name))
+    }
+
+    pub(crate) fn add_team(&mut self, name: &str) -> Result<(), Error> {
+        if self.teams.contains_key(name) {
+            return Err(Error::TeamAlreadyExists(name.to_string()))
+        }
+        self.teams.insert(name.to_string(), Team::new(name));
+        Ok(())
+    }
+
+    /// Remove team `name`, leaving the tasks assigned to it without a team
+    pub(crate) fn remove_team(&mut self, name: &str) -> Result<Team, Error> {
+        let team = self.teams.remove(name)
+            .ok_or_else(|| Error::TeamNotFound(name.to_string()))?;
+        self.team_tasks.retain(|_, team| team != name);
+        Ok(team)
+    }
+
+    pub(crate) fn join_team(&mut self, team: &str, name: &str) -> Result<(), Error> {
+        self.get(name)?;
+        self.teams
+            .get_mut(team)
+            .ok_or_else(|| Error::TeamNotFound(team.to_string()))?
+            .add_member(name);
+        Ok(())
+    }
+
+    pub(crate) fn leave_team(&mut self, team: &str, name: &str) -> Result<(), Error> {
+        let removed = self.teams
+            .get_mut(team)
+            .ok_or_else(|| Error::TeamNotFound(team.to_string()))?
+            .remove_member(name);
+        if !removed {
+            return Err(Error::NotInTeam(team.to_string(), name.to_string()))
+        }
+        Ok(())
+    }
+
+    /// Team leaf `id` is assigned to as a whole, if any
+    pub fn assigned_team(&self, id: &TaskId) -> Option<&str> {
+        self.team_tasks.get(id).map(|team| team.as_str())
+    }
+
+    /// Team answerable for task `id`: the one it is assigned to, or else the first team, by
+    /// name, one of its assignees is in
+    pub fn owning_team(&self, id: &TaskId) -> Option<&str> {
+        self.assigned_team(id).or_else(|| {
+            self.teams
+                .values()
+                .find(|team| self.assignees(id).any(|member| team.has_member(member.name())))
+                .map(|team| team.name())
+        })
+    }
+
+    /// Assign leaf `id` to team `team`, in place of the team it was assigned to if any
+    pub(crate) fn assign_team(&mut self, id: TaskId, team: &str, tasks: &Tasks) -> Result<(), Error> {
+        if tasks.get(&id)?.is_trunk() {
+            return Err(Error::TrunkCannotAssignTeam(id))
+        }
+        self.team(team)?;
+        self.team_tasks.insert(id, team.to_string());
+        Ok(())
+    }
+
+    pub(crate) fn unassign_team(&mut self, id: &TaskId) -> Result<String, Error> {
+        self.team_tasks
+            .remove(id)
+            .ok_or_else(|| Error::NoTeamAssigned(id.clone()))
     }
 
     pub(crate) fn get_mut(&mut self, name: &str) -> Result<&mut Member, Error> {
@@ -51,12 +242,30 @@ impl Members {
     }
 
     pub(crate) fn assign_task_to_member(&mut self, id: TaskId, name: &str, tasks: &mut Tasks) -> Result<(), Error> {
+        self.assign_with_allocation(id, name, FULL_ALLOCATION, tasks)
+    }
+
+    /// Assign member `name` to do `allocation` percent of task `id`, or change their allocation
+    /// if they already are. The allocations on a task can't add up to more than 100%.
+    pub(crate) fn assign_with_allocation(&mut self, id: TaskId, name: &str, allocation: u32, tasks: &mut Tasks) -> Result<(), Error> {
 
         if tasks.get(&id)?.is_trunk() {
             return Err(Error::TrunkCannotAddMember(id.clone()))
         }
+        if !(1..=100).contains(&allocation) {
+            return Err(Error::BadAllocation(allocation))
+        }
+        let total = self
+            .assignees(&id)
+            .filter(|member| member.name() != name)
+            .filter_map(|member| member.allocation(&id))
+            .sum::<u32>() + allocation;
+        if total > 100 {
+            return Err(Error::OverAllocatedTask(id, total))
+        }
 
-        self.get_mut(name)?.add_task(id);
+        self.get_mut(name)?.add_task(id.clone(), allocation);
+        self.assignees.entry(id).or_default().insert(name.to_string());
         Ok(())
     }
 
@@ -67,10 +276,97 @@ impl Members {
         } else if !self.get(name)?.is_assigned_to(id) {
             return Err(Error::CannotRemoveMemberFromTask(id.clone(), name.to_string()))
         }
-        Ok(self.get_mut(name)?.remove_task(id))
+        self.get_mut(name)?.remove_task(id);
+        self.unindex(id, name);
+        Ok(())
+    }
+
+    pub(crate) fn move_assignments(&mut self, from: &TaskId, to: &TaskId) {
+        let Some(names) = self.assignees.remove(from) else {
+            return;
+        };
+        for name in &names {
+            let member = self.members.get_mut(name).unwrap();
+            if let Some(allocation) = member.remove_task(from) {
+                member.add_task(to.clone(), allocation);
+            }
+        }
+        self.assignees.entry(to.clone()).or_default().extend(names);
+    }
+
+    pub(crate) fn move_team_assignment(&mut self, from: &TaskId, to: &TaskId) {
+        if let Some(team) = self.team_tasks.remove(from) {
+            self.team_tasks.insert(to.clone(), team);
+        }
+    }
+
+    pub(crate) fn validate(&self, tasks: &Tasks) -> Vec<Violation> {
+        let mut names = self.members.keys().collect::<Vec<_>>();
+        names.sort();
+        let mut team_tasks = self.team_tasks.iter().collect::<Vec<_>>();
+        team_tasks.sort_by(|(a, _), (b, _)| a.as_vec().cmp(b.as_vec()));
+        let mut unknown_loggers = tasks
+            .get_tasks()
+            .flat_map(|task| task.work_logs().iter().map(move |log| (task.id(), &log.member)))
+            .filter(|(_, member)| !self.members.contains_key(*member))
+            .collect::<Vec<_>>();
+        unknown_loggers.sort_by(|(a, x), (b, y)| a.as_vec().cmp(b.as_vec()).then(x.cmp(y)));
+        unknown_loggers.dedup();
+        let logs = unknown_loggers
+            .into_iter()
+            .map(|(id, member)| Violation::UnknownWorkLogMember { id: id.clone(), member: member.clone() });
+        let mut assigned = self.assignees.keys().collect::<Vec<_>>();
+        assigned.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+        let over_allocated = assigned
+            .into_iter()
+            .map(|id| (id, self.assignees(id).filter_map(|member| member.allocation(id)).sum::<u32>()))
+            .filter(|(_, total)| *total > 100)
+            .map(|(id, total)| Violation::OverAllocatedTask { id: id.clone(), total });
+        let team_violations = self.teams
+            .values()
+            .flat_map(|team| team
+                .members()
+                .filter(|name| !self.members.contains_key(*name))
+                .map(|name| Violation::UnknownTeamMember { team: team.name().to_string(), member: name.clone() }))
+            .chain(team_tasks
+                .into_iter()
+                .filter(|(id, team)| tasks.get(id).is_err() || !self.teams.contains_key(*team))
+                .map(|(id, team)| Violation::UnknownTeamTask { team: team.clone(), id: id.clone() }))
+            .collect::<Vec<_>>();
+        names.into_iter()
+            .flat_map(|name| {
+                let mut ids = self.members[name].task_ids().collect::<Vec<_>>();
+                ids.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+                ids.into_iter().filter_map(move |id| match tasks.get(id) {
+                    Err(_) => Some(Violation::UnknownTaskAssignment { member: name.clone(), id: id.clone() }),
+                    Ok(task) if task.is_trunk() => Some(Violation::TrunkAssignment { member: name.clone(), id: id.clone() }),
+                    Ok(_) => None
+                })
+            })
+            .chain(team_violations)
+            .chain(logs)
+            .chain(over_allocated)
+            .collect()
     }
 
+    /// Unassign members and teams from tasks that don't exist, and drop unknown members and
+    /// teams from teams and their tasks
+    pub(crate) fn repair(&mut self, tasks: &Tasks) {
+        self.members.values_mut().for_each(|member| member.retain_tasks(|id| tasks.get(id).is_ok()));
+        let (members, teams) = (&self.members, &mut self.teams);
+        teams.values_mut().for_each(|team| team.retain_members(|name| members.contains_key(name)));
+        let teams = &self.teams;
+        self.team_tasks.retain(|id, team| tasks.get(id).is_ok() && teams.contains_key(team));
+        self.reindex();
+    }
+
+    /// Unassign member `name` from their tasks and remove them. Members who logged hours stay,
+    /// since their rates price those hours.
     pub(crate) fn remove_member(&mut self, name: &str, tasks: &mut Tasks) -> Result<Member, Error> {
+        self.get(name)?;
+        if let Some(task) = tasks.get_tasks().find(|task| task.work_logs().iter().any(|log| log.member == name)) {
+            return Err(Error::MemberHasLoggedHours(name.to_string(), task.id().clone()))
+        }
 
         self.get(name)?
             .task_ids()
@@ -78,8 +374,175 @@ impl Members {
             .collect::<Vec<_>>()
             .iter()
             .try_for_each(|id| {
-                self.remove_member_from_task(&id, name, tasks)
+                self.remove_member_from_task(id, name, tasks)
             })?;
         self.remove(name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::prelude::{Project, SplitAmounts};
+
+    #[test]
+    fn allocations() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Backend"),
+            ("", "Frontend"),
+        ]).unwrap();
+        let (backend, frontend) = (TaskId::parse("1").unwrap(), TaskId::parse("2").unwrap());
+        let mut members = Members::new();
+        members.insert("alice".to_string()).unwrap();
+        members.insert("bob".to_string()).unwrap();
+        members.assign_with_allocation(backend.clone(), "alice", 70, &mut tasks).unwrap();
+        members.assign_with_allocation(backend.clone(), "bob", 30, &mut tasks).unwrap();
+        members.assign_task_to_member(frontend.clone(), "bob", &mut tasks).unwrap();
+        assert_eq!(members.assign_with_allocation(frontend.clone(), "alice", 0, &mut tasks), Err(Error::BadAllocation(0)));
+        assert_eq!(members.assign_with_allocation(frontend.clone(), "alice", 10, &mut tasks), Err(Error::OverAllocatedTask(frontend.clone(), 110)));
+        assert_eq!(members.assign_with_allocation(backend.clone(), "alice", 80, &mut tasks), Err(Error::OverAllocatedTask(backend.clone(), 110)));
+        // an assignee's own allocation doesn't count against its change
+        members.assign_with_allocation(backend.clone(), "alice", 70, &mut tasks).unwrap();
+        assert_eq!(members.validate(&tasks), vec![]);
+
+        let names = |members: &Members, id: &TaskId| members.assignees(id).map(|m| m.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names(&members, &backend), vec!["alice", "bob"]);
+        assert_eq!(members.share(&backend, "alice"), 0.7);
+        assert_eq!(members.share(&frontend, "bob"), 1.0);
+        assert_eq!(members.share(&frontend, "alice"), 0.0);
+
+        // the lookup survives a round trip, and reads assignments saved without allocations
+        let json = serde_json::to_string(&members).unwrap();
+        let members = serde_json::from_str::<Members>(&json).unwrap();
+        assert_eq!(names(&members, &backend), vec!["alice", "bob"]);
+        assert_eq!(members.get("alice").unwrap().allocation(&backend), Some(70));
+        let legacy = r#"{"members":[["carol",{"name":"carol","tasks":[{"id":[2]}],"routine_exceptions":[]}]]}"#;
+        let mut members = serde_json::from_str::<Members>(legacy).unwrap();
+        assert_eq!(names(&members, &frontend), vec!["carol"]);
+        assert_eq!(members.get("carol").unwrap().allocation(&frontend), Some(100));
+
+        // files can still hold more than 100% on a task
+        let legacy = r#"{"members":[["carol",{"name":"carol","tasks":[{"id":[2]}],"routine_exceptions":[]}],["dave",{"name":"dave","tasks":[{"id":[2]}],"routine_exceptions":[]}]]}"#;
+        let overloaded = serde_json::from_str::<Members>(legacy).unwrap();
+        assert_eq!(overloaded.validate(&tasks), vec![Violation::OverAllocatedTask { id: frontend.clone(), total: 200 }]);
+
+        members.remove_member_from_task(&frontend, "carol", &mut tasks).unwrap();
+        assert!(!members.is_assigned(&frontend));
+    }
+
+    #[test]
+    fn rename_member() {
+        let mut project = Project::new("Project");
+        let design = TaskId::parse("1").unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.add(TaskId::get_root_id(), "Design")?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.set_rate("alice", NaiveDate::from_ymd(2022, 1, 1), 50.0)?;
+            members.assign_with_allocation(design.clone(), "alice", 60)?;
+            Ok(())
+        }).unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.log_work(&design, "alice", NaiveDate::from_ymd(2022, 2, 1), 3.0)?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(project.members_mut(|members| {
+            members.add_member("alice")?;
+            Ok(())
+        }).err(), Some(Error::MemberAlreadyExists("alice".to_string())));
+        assert_eq!(project.members_mut(|members| {
+            members.rename_member("alice", "bob")?;
+            Ok(())
+        }).err(), Some(Error::MemberAlreadyExists("bob".to_string())));
+        assert_eq!(project.members().get("alice").unwrap().allocation(&design), Some(60));
+
+        project.members_mut(|members| {
+            members.rename_member("alice", "ali")?;
+            Ok(())
+        }).unwrap();
+        assert!(project.members().get("alice").is_err());
+        let ali = project.members().get("ali").unwrap();
+        assert_eq!(ali.name(), "ali");
+        assert_eq!(ali.allocation(&design), Some(60));
+        assert_eq!(project.members().assignees(&design).map(|m| m.name()).collect::<Vec<_>>(), vec!["ali"]);
+        assert_eq!(project.tasks().get(&design).unwrap().work_logs()[0].member, "ali");
+        assert_eq!(project.logged_cost(&design), Ok(150.0));
+
+        // the logged hours would lose their rate
+        assert_eq!(project.members_mut(|members| {
+            members.remove_member("ali")?;
+            Ok(())
+        }).err(), Some(Error::MemberHasLoggedHours("ali".to_string(), design.clone())));
+        project.members.remove("ali").unwrap();
+        assert_eq!(project.validate(), vec![Violation::UnknownWorkLogMember { id: design.clone(), member: "ali".to_string() }]);
+    }
+
+    #[test]
+    fn teams() {
+        let mut project = Project::new("Project");
+        let (api, docs) = (TaskId::parse("1").unwrap(), TaskId::parse("2").unwrap());
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "API"),
+                ("", "Docs"),
+            ])?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.add_team("backend")?;
+            members.add_team("writers")?;
+            members.join_team("backend", "alice")?;
+            members.join_team("backend", "bob")?;
+            members.join_team("writers", "bob")?;
+            members.assign_task_to_member(api.clone(), "bob")?;
+            members.assign_team(docs.clone(), "writers")?;
+            Ok(())
+        }).unwrap();
+        let members = project.members();
+        assert_eq!(members.owning_team(&api), Some("backend"));
+        assert_eq!(members.owning_team(&docs), Some("writers"));
+        assert_eq!(members.teams_of("bob").map(|team| team.name()).collect::<Vec<_>>(), vec!["backend", "writers"]);
+        assert!(project.tasks_mut(|tasks| {
+            tasks.remove(&docs)?;
+            Ok(())
+        }).is_err());
+
+        // renames and removals show in the teams, splits carry the team assignment along
+        project.members_mut(|members| {
+            members.rename_member("bob", "robert")?;
+            members.remove_member("alice")?;
+            Ok(())
+        }).unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.split(&docs, &["Guide", "Reference"], &SplitAmounts::Even, &SplitAmounts::Even, 2)?;
+            Ok(())
+        }).unwrap();
+        let members = project.members();
+        assert_eq!(members.team("backend").unwrap().members().collect::<Vec<_>>(), vec!["robert"]);
+        assert_eq!(members.assigned_team(&docs), None);
+        assert_eq!(members.assigned_team(&TaskId::parse("2.2").unwrap()), Some("writers"));
+        assert_eq!(project.members_mut(|members| {
+            members.leave_team("writers", "alice")?;
+            Ok(())
+        }).err(), Some(Error::NotInTeam("writers".to_string(), "alice".to_string())));
+        assert_eq!(project.members_mut(|members| {
+            members.assign_team(docs.clone(), "writers")?;
+            Ok(())
+        }).err(), Some(Error::TrunkCannotAssignTeam(docs.clone())));
+
+        project.members_mut(|members| {
+            members.remove_team("writers")?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(project.members().owning_team(&TaskId::parse("2.2").unwrap()), None);
+        assert!(project.validate().is_empty());
+    }
+}
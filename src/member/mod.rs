@@ -1,72 +1,292 @@
 pub(crate) mod members;
+pub mod availability;
+pub mod exception;
+pub mod capacity;
+pub mod skill;
+pub mod rate;
+pub mod assignment;
+pub mod team;
 
-use std::collections::{HashSet, HashMap};
+use std::collections::{HashMap, BTreeMap, BTreeSet};
 use std::fmt::Display;
 
-use chrono::{Utc, DateTime, NaiveDate, NaiveDateTime};
+use chrono::{NaiveDate, Duration};
 use serde::{Serialize, Deserialize};
-use serde_with::serde_as;
 
-use crate::task::task_id::TaskId;
+use crate::{task::task_id::TaskId, error::Error};
+
+use self::{assignment::{serialize_assignments, deserialize_assignments}, skill::Proficiency, rate::Rate, availability::Availability, exception::{RoutineException, Recurrence, deserialize_exceptions}};
 
-#[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Member {
     name: String,
-    weekly_cost: f64,
-    #[serde_as(as = "HashSet<_>")]
-    tasks: HashSet<TaskId>,
-    #[serde_as(as = "Vec<(_, _)>")]
-    routine_exceptions: HashMap<NaiveDateTime, f64>
+    #[serde(default)]
+    availability: Availability,
+    /// Allocation on each task, in percent
+    #[serde(serialize_with = "serialize_assignments", deserialize_with = "deserialize_assignments")]
+    tasks: HashMap<TaskId, u32>,
+    /// Later exceptions win over earlier ones on the days they share
+    #[serde(default, deserialize_with = "deserialize_exceptions")]
+    routine_exceptions: Vec<RoutineException>,
+    #[serde(default)]
+    roles: BTreeSet<String>,
+    #[serde(default)]
+    skills: BTreeMap<String, Proficiency>,
+    /// Sorted by the day each rate starts on
+    #[serde(default)]
+    rates: Vec<Rate>
 }
 
 impl Member {
     pub(crate) fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
-            weekly_cost: 0.0,
-            tasks: HashSet::new(),
-            routine_exceptions: HashMap::new()
+            availability: Availability::default(),
+            tasks: HashMap::new(),
+            routine_exceptions: Vec::new(),
+            roles: BTreeSet::new(),
+            skills: BTreeMap::new(),
+            rates: Vec::new()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn availability(&self) -> &Availability {
+        &self.availability
+    }
+
+    pub(crate) fn availability_mut(&mut self) -> &mut Availability {
+        &mut self.availability
+    }
+
+    /// Hours the member can work on `date`: the routine exception for that day if there is
+    /// one, their weekly availability otherwise
+    pub fn hours_on(&self, date: &NaiveDate) -> f64 {
+        self.get_routine_exception(date)
+            .map(|exception| exception.hours)
+            .unwrap_or_else(|| self.availability.hours_on(date))
+    }
+
+    pub fn roles(&self) -> impl Iterator<Item=&String> {
+        self.roles.iter()
+    }
+
+    pub(crate) fn add_role(&mut self, role: &str) {
+        self.roles.insert(role.to_string());
+    }
+
+    pub(crate) fn remove_role(&mut self, role: &str) -> Result<(), Error> {
+        if !self.roles.remove(role) {
+            return Err(Error::RoleNotFound(self.name.clone(), role.to_string()))
+        }
+        Ok(())
+    }
+
+    pub fn skills(&self) -> &BTreeMap<String, Proficiency> {
+        &self.skills
+    }
+
+    pub fn proficiency(&self, skill: &str) -> Option<Proficiency> {
+        self.skills.get(skill).copied()
+    }
+
+    pub(crate) fn set_skill(&mut self, skill: &str, level: Proficiency) {
+        self.skills.insert(skill.to_string(), level);
+    }
+
+    pub(crate) fn remove_skill(&mut self, skill: &str) -> Result<Proficiency, Error> {
+        self.skills
+            .remove(skill)
+            .ok_or_else(|| Error::SkillNotFound(self.name.clone(), skill.to_string()))
+    }
+
+    /// Skills in `required` the member lacks or has below the required level
+    pub fn missing_skills<'a>(&self, required: &'a BTreeMap<String, Proficiency>) -> Vec<(&'a String, Proficiency)> {
+        required
+            .iter()
+            .filter(|(skill, level)| self.proficiency(skill).is_none_or(|proficiency| proficiency < **level))
+            .map(|(skill, level)| (skill, *level))
+            .collect()
+    }
+
+    pub fn rates(&self) -> &[Rate] {
+        &self.rates
+    }
+
+    /// Hourly rate in effect on `date`, if one started by then
+    pub fn rate_on(&self, date: &NaiveDate) -> Option<f64> {
+        self.rates
+            .iter()
+            .rev()
+            .find(|rate| rate.since <= *date)
+            .map(|rate| rate.hourly)
+    }
+
+    /// Change the hourly rate from `since` on, replacing the change made that same day if any
+    pub(crate) fn set_rate(&mut self, since: NaiveDate, hourly: f64) -> Result<(), Error> {
+        if !hourly.is_finite() || hourly < 0.0 {
+            return Err(Error::BadRate(hourly.to_string()))
+        }
+        match self.rates.binary_search_by_key(&since, |rate| rate.since) {
+            Ok(index) => self.rates[index].hourly = hourly,
+            Err(index) => self.rates.insert(index, Rate { since, hourly }),
         }
+        Ok(())
+    }
+
+    pub(crate) fn remove_rate(&mut self, since: &NaiveDate) -> Result<Rate, Error> {
+        let index = self.rates
+            .binary_search_by_key(since, |rate| rate.since)
+            .map_err(|_| Error::RateNotFound(self.name.clone(), *since))?;
+        Ok(self.rates.remove(index))
     }
 
     pub fn is_assigned_to(&self, task_id: &TaskId) -> bool {
-        self.tasks.contains(task_id)
+        self.tasks.contains_key(task_id)
     }
 
     pub fn task_ids(&self) -> impl Iterator<Item=&TaskId> + '_ {
-        self.tasks.iter()
+        self.tasks.keys()
+    }
+
+    /// Percentage of task `task_id` the member does, if assigned to it
+    pub fn allocation(&self, task_id: &TaskId) -> Option<u32> {
+        self.tasks.get(task_id).copied()
     }
 
-    pub(crate) fn add_task(&mut self, task_id: TaskId) {
-        self.tasks.insert(task_id);
+    /// Assign the task, or change the allocation on it
+    pub(crate) fn add_task(&mut self, task_id: TaskId, allocation: u32) {
+        self.tasks.insert(task_id, allocation);
     }
 
-    pub(crate) fn remove_task(&mut self, task_id: &TaskId) {
-        self.tasks.remove(task_id);
+    pub(crate) fn remove_task(&mut self, task_id: &TaskId) -> Option<u32> {
+        self.tasks.remove(task_id)
     }
 
-    fn to_datetime(date: &NaiveDate) -> NaiveDateTime {
-        date.and_hms(0, 0, 0)
+    pub(crate) fn retain_tasks<F: FnMut(&TaskId) -> bool>(&mut self, mut func: F) {
+        self.tasks.retain(|id, _| func(id));
     }
 
-    pub(crate) fn add_routine_exception(&mut self, date: &NaiveDate, cost: f64) {
-        self.routine_exceptions.insert(Member::to_datetime(date), cost);
+    pub fn routine_exceptions(&self) -> &[RoutineException] {
+        &self.routine_exceptions
     }
 
-    pub(crate) fn remove_routine_exception(&mut self, date: &NaiveDate) {
-        self.routine_exceptions.remove(&Member::to_datetime(date));
+    pub(crate) fn add_routine_exception(&mut self, exception: RoutineException) -> Result<(), Error> {
+        exception.validate()?;
+        self.routine_exceptions.push(exception);
+        Ok(())
     }
 
-    pub(crate) fn get_routine_exception(&self, date: &NaiveDate) -> Option<&f64> {
-        self.routine_exceptions.get(&Member::to_datetime(date))
+    pub(crate) fn replace_routine_exception(&mut self, index: usize, exception: RoutineException) -> Result<(), Error> {
+        exception.validate()?;
+        *self.routine_exceptions
+            .get_mut(index)
+            .ok_or_else(|| Error::RoutineExceptionNotFound(self.name.clone(), index))? = exception;
+        Ok(())
+    }
+
+    pub(crate) fn remove_routine_exception(&mut self, index: usize) -> Result<RoutineException, Error> {
+        if index >= self.routine_exceptions.len() {
+            return Err(Error::RoutineExceptionNotFound(self.name.clone(), index))
+        }
+        Ok(self.routine_exceptions.remove(index))
+    }
+
+    /// Clear the days from `from` to `to` of one-off exceptions, splitting the ones that go
+    /// past both ends. Recurring exceptions are only removed if they fall within the range.
+    pub(crate) fn remove_routine_exceptions_between(&mut self, from: &NaiveDate, to: &NaiveDate) {
+        let day = Duration::days(1);
+        self.routine_exceptions = std::mem::take(&mut self.routine_exceptions)
+            .into_iter()
+            .flat_map(|exception| {
+                let end = exception.last_day();
+                let overlaps = exception.start <= *to && end.is_none_or(|end| end >= *from);
+                match (exception.recurrence, end) {
+                    (_, _) if !overlaps => vec![exception],
+                    (Recurrence::Once, Some(end)) => {
+                        let mut kept = vec![];
+                        if exception.start < *from {
+                            kept.push(RoutineException { end: Some(*from - day), ..exception.clone() });
+                        }
+                        if end > *to {
+                            kept.push(RoutineException { start: *to + day, end: Some(end), ..exception });
+                        }
+                        kept
+                    },
+                    (_, Some(end)) if exception.start >= *from && end <= *to => vec![],
+                    _ => vec![exception],
+                }
+            })
+            .collect();
+    }
+
+    /// Exception that sets the hours of `date`, if any
+    pub(crate) fn get_routine_exception(&self, date: &NaiveDate) -> Option<&RoutineException> {
+        self.routine_exceptions
+            .iter()
+            .rev()
+            .find(|exception| exception.applies_on(date))
     }
 }
 
 impl Display for Member {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let tasks = self.task_ids().fold(String::new(), |acc, id| acc + &id.to_string() + " ");
+        let tasks = self.tasks.iter().fold(String::new(), |acc, (id, allocation)| match *allocation {
+            assignment::FULL_ALLOCATION => acc + &id.to_string() + " ",
+            allocation => acc + &format!("{}:{}% ", id, allocation),
+        });
         let tasks = tasks.trim_end();
         write!(f, "{} - [{}]", self.name, tasks)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    #[test]
+    fn routine_exceptions() {
+        let mut member = Member::new("alice");
+        let vacation = RoutineException::range(NaiveDate::from_ymd(2022, 8, 1), NaiveDate::from_ymd(2022, 8, 12), 0.0)
+            .with_reason("vacation");
+        member.add_routine_exception(vacation).unwrap();
+        member.add_routine_exception(RoutineException::range(NaiveDate::from_ymd(2022, 8, 10), NaiveDate::from_ymd(2022, 8, 10), 2.0)).unwrap();
+        assert_eq!(member.hours_on(&NaiveDate::from_ymd(2022, 8, 9)), 0.0);
+        assert_eq!(member.hours_on(&NaiveDate::from_ymd(2022, 8, 10)), 2.0);
+        assert_eq!(member.hours_on(&NaiveDate::from_ymd(2022, 8, 15)), 8.0);
+        assert!(member.add_routine_exception(RoutineException::weekly(NaiveDate::from_ymd(2022, 8, 5), 0, 0.0)).is_err());
+
+        // cutting the middle of the vacation leaves both ends
+        member.remove_routine_exceptions_between(&NaiveDate::from_ymd(2022, 8, 4), &NaiveDate::from_ymd(2022, 8, 10));
+        let ranges = member.routine_exceptions().iter().map(|e| (e.start.day(), e.last_day().unwrap().day())).collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(1, 3), (11, 12)]);
+        assert_eq!(member.routine_exceptions()[1].reason.as_deref(), Some("vacation"));
+        assert_eq!(member.hours_on(&NaiveDate::from_ymd(2022, 8, 10)), 8.0);
+
+        assert_eq!(member.remove_routine_exception(5), Err(Error::RoutineExceptionNotFound("alice".to_string(), 5)));
+        member.remove_routine_exception(0).unwrap();
+        assert_eq!(member.routine_exceptions().len(), 1);
+    }
+
+    #[test]
+    fn rates() {
+        let mut member = Member::new("alice");
+        member.set_rate(NaiveDate::from_ymd(2022, 7, 1), 60.0).unwrap();
+        member.set_rate(NaiveDate::from_ymd(2022, 1, 1), 50.0).unwrap();
+        assert!(member.set_rate(NaiveDate::from_ymd(2022, 1, 1), -1.0).is_err());
+        assert_eq!(member.rate_on(&NaiveDate::from_ymd(2021, 12, 31)), None);
+        assert_eq!(member.rate_on(&NaiveDate::from_ymd(2022, 6, 30)), Some(50.0));
+        assert_eq!(member.rate_on(&NaiveDate::from_ymd(2022, 7, 1)), Some(60.0));
+
+        member.set_rate(NaiveDate::from_ymd(2022, 7, 1), 65.0).unwrap();
+        assert_eq!(member.rates().len(), 2);
+        assert_eq!(member.rate_on(&NaiveDate::from_ymd(2023, 1, 1)), Some(65.0));
+        member.remove_rate(&NaiveDate::from_ymd(2022, 7, 1)).unwrap();
+        assert_eq!(member.rate_on(&NaiveDate::from_ymd(2023, 1, 1)), Some(50.0));
+        assert_eq!(member.remove_rate(&NaiveDate::from_ymd(2022, 7, 1)), Err(Error::RateNotFound("alice".to_string(), NaiveDate::from_ymd(2022, 7, 1))));
+    }
+}
@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+/// Hourly rate of a member from a date on, until the next change
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rate {
+    pub since: NaiveDate,
+    pub hourly: f64,
+}
+
+impl Display for Rate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/h since {}", self.hourly, self.since)
+    }
+}
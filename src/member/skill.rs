@@ -0,0 +1,64 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Serialize, Deserialize};
+
+use crate::error::Error;
+
+/// How well a member masters a skill, or how well a task requires it to be mastered
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Proficiency {
+    #[default]
+    Beginner = 1,
+    Intermediate = 2,
+    Advanced = 3,
+    Expert = 4,
+}
+
+impl Proficiency {
+    /// Share of `required` that this level covers, capped at 1
+    pub fn coverage(&self, required: Proficiency) -> f64 {
+        (*self as u8 as f64 / required as u8 as f64).min(1.0)
+    }
+}
+
+impl Display for Proficiency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Proficiency::Beginner => write!(f, "beginner"),
+            Proficiency::Intermediate => write!(f, "intermediate"),
+            Proficiency::Advanced => write!(f, "advanced"),
+            Proficiency::Expert => write!(f, "expert"),
+        }
+    }
+}
+
+impl FromStr for Proficiency {
+    type Err = Error;
+
+    /// Either the name of the level or its number, from 1 to 4
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "1" | "beginner" => Ok(Proficiency::Beginner),
+            "2" | "intermediate" => Ok(Proficiency::Intermediate),
+            "3" | "advanced" => Ok(Proficiency::Advanced),
+            "4" | "expert" => Ok(Proficiency::Expert),
+            _ => Err(Error::BadProficiency(s.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proficiency() {
+        assert_eq!(Proficiency::from_str("Advanced"), Ok(Proficiency::Advanced));
+        assert_eq!(Proficiency::from_str("2"), Ok(Proficiency::Intermediate));
+        assert!(Proficiency::from_str("guru").is_err());
+        assert!(Proficiency::Expert > Proficiency::Advanced);
+        assert_eq!(Proficiency::Intermediate.coverage(Proficiency::Expert), 0.5);
+        assert_eq!(Proficiency::Expert.coverage(Proficiency::Beginner), 1.0);
+    }
+}
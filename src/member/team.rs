@@ -0,0 +1,50 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use serde::{Serialize, Deserialize};
+
+/// Named group of members, which can also own tasks as a whole
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Team {
+    name: String,
+    members: BTreeSet<String>,
+}
+
+impl Team {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            members: BTreeSet::new()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Names of the members, sorted
+    pub fn members(&self) -> impl Iterator<Item=&String> {
+        self.members.iter()
+    }
+
+    pub fn has_member(&self, name: &str) -> bool {
+        self.members.contains(name)
+    }
+
+    pub(crate) fn add_member(&mut self, name: &str) {
+        self.members.insert(name.to_string());
+    }
+
+    pub(crate) fn remove_member(&mut self, name: &str) -> bool {
+        self.members.remove(name)
+    }
+
+    pub(crate) fn retain_members<F: FnMut(&String) -> bool>(&mut self, func: F) {
+        self.members.retain(func);
+    }
+}
+
+impl Display for Team {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - [{}]", self.name, self.members.iter().cloned().collect::<Vec<_>>().join(" "))
+    }
+}
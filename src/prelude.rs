@@ -1,19 +1,41 @@
 pub use crate::{
     error::Error,
     util,
-    project::Project,
+    project::{Project, ReadyOrder},
     task::{
         Task,
+        TaskStatus,
+        MilestoneStatus,
+        SplitAmounts,
         task_id::TaskId,
-        tasks::Tasks
+        estimate::{Estimate, HOURS_PER_DAY},
+        dependency::{DependencyType, DependencyLink},
+        work_log::WorkLog,
+        schedule::{TaskTimes, CriticalPath},
+        simulation::{SimulationOptions, SimulationReport},
+        tasks::{Tasks, ViewOptions}
     },
     member::{
         Member,
+        availability::Availability,
+        exception::{RoutineException, Recurrence},
+        capacity::DayCapacity,
+        skill::Proficiency,
+        rate::Rate,
+        team::Team,
         members::Members
     },
+    validation::Violation,
+    impact::{SlipImpact, ImpactedTask},
+    blocking::{Blocker, BlockedTask, WaitingMatrix},
+    calendar::Calendar,
+    scheduler::PlannedTask,
+    suggestion::Suggestion,
+    workload::{Workload, MemberLoad},
+    team_metrics::TeamMetrics,
+    dashboard::{MemberDashboard, AssignedTask, SprintCommitment},
     interface::{
         task_execution::TaskExecution,
         member_execution::MemberExecution,
-        sprint_execution::SprintExecution,
     }
 };
@@ -2,14 +2,29 @@ use serde::{Deserialize, Serialize};
 
 use std::io::Write;
 
-use crate::{prelude::{Tasks, Members, Error}, interface::{task_execution::TaskExecution, member_execution::MemberExecution}, sprint::sprint::Sprints, util::DEFAULT_FILENAME};
+use chrono::NaiveDate;
+
+use crate::{prelude::{Tasks, Task, Members, Error, Calendar}, interface::{task_execution::TaskExecution, member_execution::MemberExecution}, sprint::sprint::Sprints, util::DEFAULT_FILENAME};
+
+/// How to sort the tasks that are ready to be worked on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadyOrder {
+    /// Highest priority first
+    Priority,
+    /// Earliest due date first, tasks without one last
+    DueDate,
+    /// Least critical path slack first
+    Slack
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Project {
 
     pub(crate) tasks: Tasks,
     pub(crate) members: Members,
-    pub(crate) sprints: Sprints
+    pub(crate) sprints: Sprints,
+    #[serde(default)]
+    pub(crate) calendar: Calendar
 }
 
 impl Project {
@@ -18,13 +33,14 @@ impl Project {
         Self {
             tasks: Tasks::new(name),
             members: Members::new(),
-            sprints: Sprints::new()
+            sprints: Sprints::new(),
+            calendar: Calendar::default()
         }
     }
 
     pub fn load(filename: &str) -> Result<Self, Error> {
-        let json_contents = std::fs::read_to_string(&filename)
-            .or_else(|_| Err(Error::FileRead(filename.to_string())))?;
+        let json_contents = std::fs::read_to_string(filename)
+            .map_err(|_| Error::FileRead(filename.to_string()))?;
         Self::from_json(&json_contents)
     }
 
@@ -34,9 +50,9 @@ impl Project {
 
     pub fn save_to(&mut self, filename: &str) -> Result<&mut Self, Error> {
         let mut file = std::fs::File::create(filename)
-            .or_else(|_| Err(Error::OpenFile(filename.to_string())))?;
+            .map_err(|_| Error::OpenFile(filename.to_string()))?;
         write!(file, "{}", self.to_json()?)
-            .or_else(|_| Err(Error::FileWrite(filename.to_string())))?;
+            .map_err(|_| Error::FileWrite(filename.to_string()))?;
         Ok(self)
     }
 
@@ -52,6 +68,24 @@ impl Project {
         &self.members
     }
 
+    pub fn calendar(&self) -> &Calendar {
+        &self.calendar
+    }
+
+    pub fn add_holiday(&mut self, date: NaiveDate) -> Result<&mut Self, Error> {
+        if !self.calendar.add_holiday(date) {
+            return Err(Error::HolidayAlreadyExists(date));
+        }
+        Ok(self)
+    }
+
+    pub fn remove_holiday(&mut self, date: &NaiveDate) -> Result<&mut Self, Error> {
+        if !self.calendar.remove_holiday(date) {
+            return Err(Error::HolidayNotFound(*date));
+        }
+        Ok(self)
+    }
+
     pub fn tasks_mut<F>(&mut self, mut func: F) -> Result<&mut Self, Error>
     where F: FnMut(&mut TaskExecution<'_>) -> Result<(), Error> {
         {
@@ -70,17 +104,87 @@ impl Project {
         Ok(self)
     }
 
+    /// Leaves that aren't done and whose dependencies are, optionally only the ones assigned to
+    /// member `name`
+    pub fn ready_queue(&self, name: Option<&str>, order: ReadyOrder) -> Result<Vec<&Task>, Error> {
+        let member = name.map(|name| self.members.get(name)).transpose()?;
+        let mut ready = self.tasks
+            .get_ready_tasks()
+            .filter(|task| member.is_none_or(|member| member.is_assigned_to(task.id())))
+            .collect::<Vec<_>>();
+
+        let by_due_date = |a: &&Task, b: &&Task| (a.due_date().is_none(), a.due_date()).cmp(&(b.due_date().is_none(), b.due_date()));
+        let by_id = |a: &&Task, b: &&Task| a.id().as_vec().cmp(b.id().as_vec());
+        match order {
+            ReadyOrder::Priority => ready.sort_by(|a, b| b.priority().cmp(&a.priority()).then(by_due_date(a, b)).then(by_id(a, b))),
+            ReadyOrder::DueDate => ready.sort_by(|a, b| by_due_date(a, b).then(b.priority().cmp(&a.priority())).then(by_id(a, b))),
+            ReadyOrder::Slack => {
+                let critical_path = self.tasks.critical_path();
+                let slack = |task: &Task| critical_path.times(task.id()).map_or(0.0, |t| t.total_slack());
+                ready.sort_by(|a, b| slack(a).partial_cmp(&slack(b)).unwrap().then(by_due_date(a, b)).then(by_id(a, b)))
+            }
+        }
+        Ok(ready)
+    }
+
     fn from_json(project_str: &str) -> Result<Self, Error> {
         serde_json::from_str(project_str)
-            .or_else(|_| Err(Error::ParseJsonContents(project_str.to_string())))
+            .map_err(|_| Error::ParseJsonContents(project_str.to_string()))
     }
 
     fn to_json(&self) -> Result<String, Error> {
         serde_json::to_string(self)
-            .or_else(|_| Err(Error::ParseProjectContents))
+            .map_err(|_| Error::ParseProjectContents)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::prelude::TaskId;
+
+    #[test]
+    fn ready_queue() {
+        let mut project = Project::new("Project");
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Design"),
+                ("", "Build"),
+                ("", "Docs"),
+                ("", "Tests"),
+            ])?;
+            tasks.add_dependency(&TaskId::parse("2")?, &TaskId::parse("1")?)?;
+            tasks.priority(&TaskId::parse("3")?, 2)?;
+            tasks.priority(&TaskId::parse("4")?, 1)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.assign_task_to_member(TaskId::parse("4")?, "alice")?;
+            Ok(())
+        }).unwrap();
+
+        let ids = |tasks: Vec<&Task>| tasks.iter().map(|t| t.id().to_string()).collect::<Vec<_>>();
+        assert_eq!(ids(project.ready_queue(None, ReadyOrder::Priority).unwrap()), vec!["3", "4", "1"]);
+        assert_eq!(ids(project.ready_queue(Some("alice"), ReadyOrder::Priority).unwrap()), vec!["4"]);
+        assert!(project.ready_queue(Some("bob"), ReadyOrder::Priority).is_err());
+
+        project.tasks_mut(|tasks| {
+            tasks.done(&TaskId::parse("1")?, 1.0)?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(ids(project.ready_queue(None, ReadyOrder::Priority).unwrap()), vec!["3", "4", "2"]);
+
+        // the priority of a leaf goes to its first child, and trunks can't get one
+        let tests = TaskId::parse("4").unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.add(tests.clone(), "Unit tests")?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(project.tasks().get(&TaskId::parse("4.1").unwrap()).unwrap().priority(), 1);
+        assert_eq!(project.tasks_mut(|tasks| {
+            tasks.priority(&tests, 3)?;
+            Ok(())
+        }).err(), Some(Error::TrunkCannotHavePriority(tests)));
+    }
 }
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Duration};
+
+use crate::{prelude::{Project, Task, TaskId, TaskStatus, Member, DependencyType, Error, HOURS_PER_DAY}, task::schedule::SLACK_EPSILON};
+
+/// How far past the project start leveling looks for free time before giving up on a task
+const HORIZON_DAYS: i64 = 3650;
+
+/// Dates given to a leaf by resource leveling
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedTask {
+    pub id: TaskId,
+    /// First day of work
+    pub start: NaiveDate,
+    /// Last day of work
+    pub finish: NaiveDate,
+}
+
+/// Hours booked for a member on a day
+type Bookings<'a> = HashMap<(&'a str, NaiveDate), f64>;
+
+/// Placement of a leaf while leveling
+#[derive(Clone, Copy)]
+struct Slot {
+    start: NaiveDate,
+    finish: NaiveDate,
+    /// First day a finish-to-start dependent can start on: the day after the last day of work,
+    /// or the start itself for tasks without effort
+    next: NaiveDate,
+}
+
+impl Project {
+
+    /// Resource-leveled schedule of the leaves that aren't done, with work starting on `start`.
+    ///
+    /// Leaves are placed one at a time, picking among the ones whose dependencies are placed the
+    /// one with the least critical path slack, then the highest priority. A task starts as soon
+    /// as its dependency edges allow, and its effort is worked off the free hours its assignees
+    /// have left each day, so that nobody is booked twice. Unassigned tasks take `HOURS_PER_DAY`
+    /// on every working day of the project calendar; nobody works on holidays.
+    pub fn plan_schedule(&self, start: NaiveDate) -> Result<Vec<PlannedTask>, Error> {
+        let tasks = &self.tasks;
+        let critical_path = tasks.critical_path();
+        let slack = |task: &Task| critical_path.times(task.id()).map_or(0.0, |t| t.total_slack());
+        let limit = start + Duration::days(HORIZON_DAYS);
+
+        let mut remaining = tasks
+            .get_tasks()
+            .filter(|task| task.status != TaskStatus::Done)
+            .collect::<Vec<_>>();
+        let mut placed : HashMap<&TaskId, Slot> = HashMap::with_capacity(remaining.len());
+        let mut booked = Bookings::new();
+        let mut plan = Vec::with_capacity(remaining.len());
+
+        loop {
+            let is_ready = |task: &Task| tasks
+                .leaf_dependencies(task.id())
+                .iter()
+                .all(|(id, _)| placed.contains_key(id) || tasks.get(id).is_ok_and(|t| t.status == TaskStatus::Done));
+            let next = remaining
+                .iter()
+                .enumerate()
+                .filter(|(_, task)| is_ready(task))
+                .min_by(|(_, a), (_, b)| slack(a).partial_cmp(&slack(b)).unwrap()
+                    .then(b.priority().cmp(&a.priority()))
+                    .then(a.id().as_vec().cmp(b.id().as_vec())))
+                .map(|(index, _)| index);
+            let Some(index) = next else {
+                break;
+            };
+            let task = remaining.swap_remove(index);
+
+            // dependencies that are done don't hold the task back anymore
+            let mut earliest = start;
+            let mut min_finish = None;
+            for (id, link) in tasks.leaf_dependencies(task.id()) {
+                let Some(slot) = placed.get(id) else {
+                    continue;
+                };
+                let lag = Duration::days(link.lag);
+                match link.kind {
+                    DependencyType::FinishToStart => earliest = earliest.max(slot.next + lag),
+                    DependencyType::StartToStart => earliest = earliest.max(slot.start + lag),
+                    DependencyType::FinishToFinish => min_finish = min_finish.max(Some(slot.finish + lag)),
+                    DependencyType::StartToFinish => min_finish = min_finish.max(Some(slot.start + lag)),
+                }
+            }
+
+            let assignees = self.members
+                .assignees(task.id())
+                .map(|member| (member, self.members.share(task.id(), member.name())))
+                .collect::<Vec<_>>();
+            let slot = if task.expected_effort() <= SLACK_EPSILON {
+                let mut day = min_finish.map_or(earliest, |finish| earliest.max(finish));
+                while !self.calendar.is_working_day(&day) {
+                    day = day.succ();
+                }
+                Slot { start: day, finish: day, next: day }
+            } else {
+                let mut candidate = earliest;
+                loop {
+                    let (slot, bookings) = self.fit(task.expected_effort(), &assignees, candidate, limit, &booked)
+                        .ok_or_else(|| Error::CannotSchedule(task.id().clone(), HORIZON_DAYS))?;
+                    // finish-to-finish and start-to-finish edges push the whole task later
+                    if min_finish.is_none_or(|finish| slot.finish >= finish) {
+                        bookings.into_iter().for_each(|(key, hours)| *booked.entry(key).or_default() += hours);
+                        break slot;
+                    }
+                    candidate = candidate.succ();
+                }
+            };
+            placed.insert(task.id(), slot);
+            plan.push(PlannedTask { id: task.id().clone(), start: slot.start, finish: slot.finish });
+        }
+        Ok(plan)
+    }
+
+    /// Work off `hours` from `from` onwards, without going past `limit`, each assignee doing
+    /// their share of them. Returns the slot and the hours it books for each assignee and day.
+    fn fit<'a>(&self, hours: f64, assignees: &[(&'a Member, f64)], from: NaiveDate, limit: NaiveDate, booked: &Bookings<'a>) -> Option<(Slot, Bookings<'a>)> {
+        let mut left = if assignees.is_empty() {
+            vec![hours]
+        } else {
+            assignees.iter().map(|(_, share)| hours * share).collect::<Vec<_>>()
+        };
+        let mut day = from;
+        let mut first = None;
+        let mut last = from;
+        let mut bookings = Bookings::new();
+        while left.iter().any(|hours| *hours > SLACK_EPSILON) {
+            if day > limit {
+                return None;
+            }
+            let mut worked = false;
+            if assignees.is_empty() {
+                if self.calendar.is_working_day(&day) {
+                    left[0] -= left[0].min(HOURS_PER_DAY);
+                    worked = true;
+                }
+            } else if !self.calendar.is_holiday(&day) {
+                for ((member, _), left) in assignees.iter().zip(left.iter_mut()) {
+                    let key = (member.name(), day);
+                    let free = member.hours_on(&day) - booked.get(&key).copied().unwrap_or(0.0);
+                    let hours = free.min(*left);
+                    if hours > SLACK_EPSILON {
+                        bookings.insert(key, hours);
+                        *left -= hours;
+                        worked = true;
+                    }
+                }
+            }
+            if worked {
+                first.get_or_insert(day);
+                last = day;
+            }
+            day = day.succ();
+        }
+        let start = first.unwrap_or(from);
+        Some((Slot { start, finish: last, next: last.succ() }, bookings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Weekday};
+
+    use super::*;
+    use crate::prelude::{Estimate, DependencyLink};
+
+    #[test]
+    fn plan_schedule() {
+        let mut project = Project::new("Project");
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Design"),
+                ("", "Backend"),
+                ("", "Frontend"),
+                ("", "Docs"),
+                ("", "Review"),
+            ])?;
+            for (id, hours) in [("1", 16.0), ("2", 16.0), ("3", 8.0), ("4", 8.0)] {
+                tasks.estimate(&TaskId::parse(id)?, Some(Estimate::new(hours)?))?;
+            }
+            tasks.add_dependency(&TaskId::parse("2")?, &TaskId::parse("1")?)?;
+            tasks.add_dependency(&TaskId::parse("3")?, &TaskId::parse("1")?)?;
+            tasks.add_dependency_with(&TaskId::parse("4")?, &TaskId::parse("3")?, DependencyLink::new(DependencyType::FinishToFinish, 0))?;
+            tasks.add_dependency(&TaskId::parse("5")?, &TaskId::parse("2")?)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.assign_task_to_member(TaskId::parse("1")?, "alice")?;
+            members.assign_task_to_member(TaskId::parse("2")?, "alice")?;
+            members.assign_task_to_member(TaskId::parse("3")?, "alice")?;
+            Ok(())
+        }).unwrap();
+        // Monday, with Wednesday off for everyone
+        let monday = NaiveDate::from_ymd(2024, 1, 1);
+        assert_eq!(monday.weekday(), Weekday::Mon);
+        project.add_holiday(NaiveDate::from_ymd(2024, 1, 3)).unwrap();
+
+        let plan = project.plan_schedule(monday).unwrap();
+        let dates = |id: &str| {
+            let task = plan.iter().find(|t| t.id == TaskId::parse(id).unwrap()).unwrap();
+            (task.start, task.finish)
+        };
+        let day = |d: u32| NaiveDate::from_ymd(2024, 1, d);
+        assert_eq!(dates("1"), (day(1), day(2)));
+        // alice works on the backend first, it's the critical task, then on the frontend
+        assert_eq!(dates("2"), (day(4), day(5)));
+        assert_eq!(dates("3"), (day(8), day(8)));
+        // docs are unassigned, so they could start right away, but have to finish with the frontend
+        assert_eq!(dates("4"), (day(8), day(8)));
+        // the review takes no effort, and the backend is done on a Friday, so it waits for Monday
+        assert_eq!(dates("5"), (day(8), day(8)));
+
+        let design = TaskId::parse("1").unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.level(monday)?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(project.tasks().get(&design).unwrap().planned_dates(), Some((day(1), day(2))));
+        project.tasks_mut(|tasks| {
+            tasks.done(&design, 16.0)?;
+            tasks.level(monday)?;
+            Ok(())
+        }).unwrap();
+        // the design is done, so it doesn't keep the dates of the first plan
+        assert_eq!(project.tasks().get(&design).unwrap().planned_dates(), None);
+        let plan = project.plan_schedule(monday).unwrap();
+        assert_eq!(plan.len(), 4);
+        let dates = |id: &str| {
+            let task = plan.iter().find(|t| t.id == TaskId::parse(id).unwrap()).unwrap();
+            (task.start, task.finish)
+        };
+        assert_eq!(dates("2"), (day(1), day(2)));
+        assert_eq!(dates("3"), (day(4), day(4)));
+    }
+
+    #[test]
+    fn plan_schedule_without_capacity() {
+        let mut project = Project::new("Project");
+        project.tasks_mut(|tasks| {
+            tasks.add(TaskId::get_root_id(), "Design")?;
+            tasks.estimate(&TaskId::parse("1")?, Some(Estimate::new(8.0)?))?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.update_availability("alice", "mon-sun=0")?;
+            members.assign_task_to_member(TaskId::parse("1")?, "alice")?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(
+            project.plan_schedule(NaiveDate::from_ymd(2024, 1, 1)),
+            Err(Error::CannotSchedule(TaskId::parse("1").unwrap(), HORIZON_DAYS)));
+    }
+}
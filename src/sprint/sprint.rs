@@ -1,13 +1,21 @@
-use chrono::{Utc, DateTime, Date, Duration};
+use std::collections::HashSet;
+
+use chrono::{Utc, DateTime, Date, Duration, NaiveDate};
 use serde::{Deserialize, Serialize};
 
-use crate::prelude::TaskId;
+use crate::prelude::{TaskId, Tasks, Violation};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Sprint {
     backlog: Vec<TaskId>,
 }
 
+impl Sprint {
+    pub(crate) fn backlog(&self) -> &[TaskId] {
+        &self.backlog
+    }
+}
+
 #[serde_with::serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Sprints {
@@ -37,6 +45,24 @@ impl Sprints {
         (start.date(), end.date())
     }
 
+    /// Start the sprints on `start_date`, each lasting `duration_in_weeks`
+    #[cfg(test)]
+    pub(crate) fn set_schedule(&mut self, start_date: DateTime<Utc>, duration_in_weeks: u64) {
+        self.start_date = start_date;
+        self.duration_in_weeks = duration_in_weeks;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn push(&mut self, backlog: Vec<TaskId>) {
+        self.sprints.push(Sprint { backlog });
+    }
+
+    /// Index of the sprint `date` falls in, if any
+    pub(crate) fn sprint_on(&self, date: &NaiveDate) -> Option<usize> {
+        self.sprints_date_times()
+            .position(|(start, end)| start.naive_utc() <= *date && *date < end.naive_utc())
+    }
+
     pub(crate) fn sprints_date_times(&self) -> impl Iterator<Item=(Date<Utc>, Date<Utc>)> + '_ {
         self.sprints
             .iter()
@@ -44,10 +70,37 @@ impl Sprints {
             .map(|(i, _)| self.sprint_to_date_time(i))
     }
 
-    pub(crate) fn product_backlog(&self) -> impl Iterator<Item=&TaskId> {
+    pub(crate) fn validate(&self, tasks: &Tasks) -> Vec<Violation> {
+        self.sprints
+            .iter()
+            .enumerate()
+            .flat_map(|(i, sprint)| {
+                sprint.backlog
+                    .iter()
+                    .filter(|id| tasks.get(id).is_err())
+                    .map(move |id| Violation::UnknownSprintTask { sprint: i, id: id.clone() })
+            })
+            .collect()
+    }
 
+    /// Drop backlog entries for tasks that don't exist
+    pub(crate) fn repair(&mut self, tasks: &Tasks) {
+        self.sprints
+            .iter_mut()
+            .for_each(|sprint| sprint.backlog.retain(|id| tasks.get(id).is_ok()));
+    }
+
+    /// Indices of the sprints whose backlog has one of `ids`, or one of their ancestors
+    pub(crate) fn sprints_containing(&self, ids: &HashSet<&TaskId>) -> Vec<usize> {
+        let ids = ids
+            .iter()
+            .flat_map(|id| id.path())
+            .collect::<HashSet<_>>();
         self.sprints
             .iter()
-            .flat_map(|sprint| &sprint.backlog)
+            .enumerate()
+            .filter(|(_, sprint)| sprint.backlog.iter().any(|id| ids.contains(id)))
+            .map(|(i, _)| i)
+            .collect()
     }
 }
@@ -0,0 +1,132 @@
+use crate::prelude::{Project, Member, TaskId, TaskStatus, Proficiency, Error};
+
+/// Member ranked as a possible assignee of a task
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub name: String,
+    /// Average share of each required skill the member covers, 1 if the task requires none
+    pub skill_match: f64,
+    /// Required skills the member lacks or has below the required level
+    pub missing_skills: Vec<(String, Proficiency)>,
+    /// Share of the remaining effort of the unfinished leaves the member is assigned to, in hours
+    pub load: f64,
+    /// Weeks of work `load` makes given the weekly availability of the member, infinite if
+    /// they are never available
+    pub load_weeks: f64,
+}
+
+impl Project {
+
+    /// Members that could take leaf `id`, best first: the ones covering more of the required
+    /// skills, then the ones with less work left. Members already assigned to the task are left
+    /// out.
+    pub fn suggest_assignees(&self, id: &TaskId) -> Result<Vec<Suggestion>, Error> {
+        let task = self.tasks.get(id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotAddMember(id.clone()));
+        }
+        let required = task.required_skills();
+        let mut suggestions = self.members
+            .members()
+            .filter(|member| !member.is_assigned_to(id))
+            .map(|member| {
+                let skill_match = if required.is_empty() {
+                    1.0
+                } else {
+                    required
+                        .iter()
+                        .map(|(skill, level)| member.proficiency(skill).map_or(0.0, |proficiency| proficiency.coverage(*level)))
+                        .sum::<f64>() / required.len() as f64
+                };
+                let load = self.remaining_effort(member);
+                let weekly_hours = member.availability().weekly_hours();
+                Suggestion {
+                    name: member.name().to_string(),
+                    skill_match,
+                    missing_skills: member.missing_skills(required).into_iter().map(|(skill, level)| (skill.clone(), level)).collect(),
+                    load,
+                    load_weeks: if weekly_hours > 0.0 { load / weekly_hours } else { f64::INFINITY },
+                }
+            })
+            .collect::<Vec<_>>();
+        suggestions.sort_by(|a, b| b.skill_match.partial_cmp(&a.skill_match).unwrap()
+            .then(a.load_weeks.partial_cmp(&b.load_weeks).unwrap())
+            .then(a.name.cmp(&b.name)));
+        Ok(suggestions)
+    }
+
+    /// Required skills of task `id` that member `name` lacks or has below the required level
+    pub fn missing_skills(&self, id: &TaskId, name: &str) -> Result<Vec<(String, Proficiency)>, Error> {
+        let task = self.tasks.get(id)?;
+        Ok(self.members
+            .get(name)?
+            .missing_skills(task.required_skills())
+            .into_iter()
+            .map(|(skill, level)| (skill.clone(), level))
+            .collect())
+    }
+
+    /// Share of the remaining effort of the unfinished leaves `member` is assigned to, in hours
+    pub(crate) fn remaining_effort(&self, member: &Member) -> f64 {
+        member
+            .task_ids()
+            .filter_map(|id| self.tasks.get(id).ok())
+            .filter(|task| task.status != TaskStatus::Done)
+            .map(|task| task.expected_effort() * self.members.share(task.id(), member.name()))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Estimate;
+
+    #[test]
+    fn suggest_assignees() {
+        let mut project = Project::new("Project");
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Backend"),
+                ("", "Frontend"),
+                ("", "Database"),
+            ])?;
+            tasks.estimate(&TaskId::parse("2")?, Some(Estimate::new(40.0)?))?;
+            tasks.require_skill(&TaskId::parse("1")?, "rust", Proficiency::Advanced)?;
+            tasks.require_skill(&TaskId::parse("1")?, "sql", Proficiency::Beginner)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.add_member("carol")?;
+            members.set_skill("alice", "rust", Proficiency::Expert)?;
+            members.set_skill("alice", "sql", Proficiency::Beginner)?;
+            members.set_skill("bob", "rust", Proficiency::Expert)?;
+            members.set_skill("bob", "sql", Proficiency::Advanced)?;
+            members.set_skill("carol", "rust", Proficiency::Beginner)?;
+            members.assign_task_to_member(TaskId::parse("2")?, "bob")?;
+            Ok(())
+        }).unwrap();
+
+        let suggestions = project.suggest_assignees(&TaskId::parse("1").unwrap()).unwrap();
+        let names = suggestions.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        // alice and bob both cover everything, but bob already has a week of work
+        assert_eq!(names, vec!["alice", "bob", "carol"]);
+        assert_eq!(suggestions[1].load_weeks, 1.0);
+        assert_eq!(suggestions[2].skill_match, (1.0 / 3.0) / 2.0);
+        assert_eq!(suggestions[2].missing_skills, vec![
+            ("rust".to_string(), Proficiency::Advanced),
+            ("sql".to_string(), Proficiency::Beginner)
+        ]);
+        assert_eq!(project.missing_skills(&TaskId::parse("1").unwrap(), "alice").unwrap(), vec![]);
+
+        // nothing required, so only the load matters
+        let names = project.suggest_assignees(&TaskId::parse("3").unwrap()).unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["alice", "carol", "bob"]);
+        assert!(project.suggest_assignees(&TaskId::get_root_id()).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Serialize, Deserialize};
+
+use crate::error::Error;
+
+/// Which end of the dependency constrains which end of the dependent task
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DependencyType {
+    /// Task starts after the dependency finishes
+    #[default]
+    FinishToStart,
+    /// Task starts after the dependency starts
+    StartToStart,
+    /// Task finishes after the dependency finishes
+    FinishToFinish,
+    /// Task finishes after the dependency starts
+    StartToFinish,
+}
+
+impl Display for DependencyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyType::FinishToStart => write!(f, "FS"),
+            DependencyType::StartToStart => write!(f, "SS"),
+            DependencyType::FinishToFinish => write!(f, "FF"),
+            DependencyType::StartToFinish => write!(f, "SF"),
+        }
+    }
+}
+
+impl FromStr for DependencyType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fs" | "finish-to-start" => Ok(DependencyType::FinishToStart),
+            "ss" | "start-to-start" => Ok(DependencyType::StartToStart),
+            "ff" | "finish-to-finish" => Ok(DependencyType::FinishToFinish),
+            "sf" | "start-to-finish" => Ok(DependencyType::StartToFinish),
+            _ => Err(Error::BadDependencyType(s.to_string()))
+        }
+    }
+}
+
+/// Type and lag of a dependency edge. Negative lags let the dependent task overlap its
+/// dependency.
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DependencyLink {
+    pub kind: DependencyType,
+    /// Delay in days
+    pub lag: i64,
+}
+
+impl DependencyLink {
+    pub fn new(kind: DependencyType, lag: i64) -> Self {
+        Self { kind, lag }
+    }
+
+    /// Earliest start of the dependent task allowed by this edge, given the earliest start and
+    /// finish of the dependency and the duration of the dependent task
+    pub(crate) fn earliest_start(&self, start: f64, finish: f64, duration: f64) -> f64 {
+        let lag = self.lag as f64;
+        match self.kind {
+            DependencyType::FinishToStart => finish + lag,
+            DependencyType::StartToStart => start + lag,
+            DependencyType::FinishToFinish => finish + lag - duration,
+            DependencyType::StartToFinish => start + lag - duration,
+        }
+    }
+
+    /// Latest finish of the dependency allowed by this edge, given the latest start and finish
+    /// of the dependent task and the duration of the dependency
+    pub(crate) fn latest_finish(&self, start: f64, finish: f64, duration: f64) -> f64 {
+        let lag = self.lag as f64;
+        match self.kind {
+            DependencyType::FinishToStart => start - lag,
+            DependencyType::StartToStart => start - lag + duration,
+            DependencyType::FinishToFinish => finish - lag,
+            DependencyType::StartToFinish => finish - lag + duration,
+        }
+    }
+}
+
+impl Display for DependencyLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.lag {
+            0 => write!(f, "{}", self.kind),
+            lag => write!(f, "{}{:+}d", self.kind, lag),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_parse() {
+        assert_eq!(DependencyLink::default().to_string(), "FS");
+        assert_eq!(DependencyLink::new(DependencyType::StartToStart, 2).to_string(), "SS+2d");
+        assert_eq!(DependencyLink::new(DependencyType::FinishToFinish, -1).to_string(), "FF-1d");
+        assert_eq!(DependencyType::from_str("sf"), Ok(DependencyType::StartToFinish));
+        assert!(DependencyType::from_str("xx").is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt::Display;
+
+use serde::{Serialize, Deserialize};
+
+use crate::error::Error;
+
+/// Working hours in a day, used to turn effort into durations
+pub const HOURS_PER_DAY: f64 = 8.0;
+
+/// Effort estimate of a leaf task, in hours. Single point estimates have all three values equal.
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    optimistic: f64,
+    most_likely: f64,
+    pessimistic: f64,
+}
+
+impl Estimate {
+
+    pub fn new(hours: f64) -> Result<Self, Error> {
+        Self::three_point(hours, hours, hours)
+    }
+
+    pub fn three_point(optimistic: f64, most_likely: f64, pessimistic: f64) -> Result<Self, Error> {
+        if !(0.0 <= optimistic && optimistic <= most_likely && most_likely <= pessimistic) {
+            return Err(Error::BadEstimate(format!("{}/{}/{}", optimistic, most_likely, pessimistic)))
+        }
+        Ok(Self { optimistic, most_likely, pessimistic })
+    }
+
+    pub fn optimistic(&self) -> f64 {
+        self.optimistic
+    }
+
+    pub fn most_likely(&self) -> f64 {
+        self.most_likely
+    }
+
+    pub fn pessimistic(&self) -> f64 {
+        self.pessimistic
+    }
+
+    pub fn is_three_point(&self) -> bool {
+        self.optimistic != self.pessimistic
+    }
+
+    /// PERT expected effort
+    pub fn expected(&self) -> f64 {
+        (self.optimistic + 4.0 * self.most_likely + self.pessimistic) / 6.0
+    }
+
+    /// PERT standard deviation
+    pub fn std_dev(&self) -> f64 {
+        (self.pessimistic - self.optimistic) / 6.0
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.std_dev().powi(2)
+    }
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_three_point() {
+            write!(f, "{}/{}/{}h", self.optimistic, self.most_likely, self.pessimistic)
+        } else {
+            write!(f, "{}h", self.most_likely)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pert() {
+        let estimate = Estimate::three_point(2.0, 4.0, 12.0).unwrap();
+        assert_eq!(estimate.expected(), 5.0);
+        assert_eq!(estimate.std_dev(), 10.0 / 6.0);
+        assert_eq!(Estimate::new(3.0).unwrap().expected(), 3.0);
+        assert_eq!(Estimate::new(3.0).unwrap().std_dev(), 0.0);
+        assert!(Estimate::three_point(5.0, 4.0, 12.0).is_err());
+        assert!(Estimate::three_point(2.0, 4.0, 3.0).is_err());
+        assert!(Estimate::new(-1.0).is_err());
+    }
+}
@@ -1,12 +1,21 @@
 pub mod task_id;
+pub mod estimate;
+pub mod dependency;
+pub mod work_log;
+pub(crate) mod schedule;
+pub(crate) mod simulation;
+pub(crate) mod reduction;
 pub(crate) mod tasks;
 
-use std::{fmt::Display, collections::HashSet};
+use std::{fmt::Display, collections::{HashSet, HashMap, BTreeMap}};
 
+use chrono::NaiveDate;
 use serde::{Serialize, Deserialize};
 use serde_with::serde_as;
 
-use self::task_id::TaskId;
+use crate::member::skill::Proficiency;
+
+use self::{task_id::TaskId, estimate::{Estimate, HOURS_PER_DAY}, dependency::DependencyLink, work_log::WorkLog};
 
 #[derive(Serialize, Deserialize)]
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -33,6 +42,36 @@ impl TaskStatus {
     }
 }
 
+/// Unreached milestones closer than this to their target date are at risk
+const MILESTONE_AT_RISK_DAYS: i64 = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MilestoneStatus {
+    Reached,
+    OnTrack,
+    AtRisk,
+    Missed
+}
+
+impl Display for MilestoneStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MilestoneStatus::Reached => write!(f, "reached"),
+            MilestoneStatus::OnTrack => write!(f, "on track"),
+            MilestoneStatus::AtRisk => write!(f, "at risk"),
+            MilestoneStatus::Missed => write!(f, "missed"),
+        }
+    }
+}
+
+/// How the planned value or actual cost of a leaf is spread across its new children when it
+/// is split
+#[derive(Clone, Debug, PartialEq)]
+pub enum SplitAmounts {
+    Even,
+    Explicit(Vec<f64>)
+}
+
 #[serde_as]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Task {
@@ -46,6 +85,45 @@ pub struct Task {
     pub(crate) dependencies: HashSet<TaskId>,
     #[serde_as(as = "HashSet<_>")]
     pub(crate) dependency_for: HashSet<TaskId>,
+    /// Type and lag of the edges in `dependencies`, keyed by dependency. Edges missing here are
+    /// finish-to-start with no lag.
+    #[serde_as(as = "Vec<(_, _)>")]
+    #[serde(default)]
+    pub(crate) dependency_links: HashMap<TaskId, DependencyLink>,
+    /// Only milestones have a target date
+    #[serde(default)]
+    pub(crate) target_date: Option<NaiveDate>,
+    /// Set on leaves, and rolled up to trunks as the latest due date of their children
+    #[serde(default)]
+    pub(crate) due_date: Option<NaiveDate>,
+    /// Only set on leaves
+    #[serde(default)]
+    pub(crate) estimate: Option<Estimate>,
+    /// Higher priorities are worked on first
+    #[serde(default)]
+    pub(crate) priority: i32,
+    /// PERT expected effort, rolled up like `planned_value`
+    #[serde(default)]
+    pub(crate) expected_effort: f64,
+    /// Variance of the PERT effort, rolled up like `planned_value`
+    #[serde(default)]
+    pub(crate) effort_variance: f64,
+    /// First and last day of work on a leaf, as set by the last resource leveling
+    #[serde(default)]
+    pub(crate) planned_dates: Option<(NaiveDate, NaiveDate)>,
+    /// Skills the assignees of a leaf need, with the least proficiency they need them at
+    #[serde(default)]
+    pub(crate) required_skills: BTreeMap<String, Proficiency>,
+    #[serde(default)]
+    pub(crate) work_logs: Vec<WorkLog>,
+    /// Set by hand, so not derived from the estimate and the rates of the assignees. Missing in
+    /// files saved before values could be derived, where any value was set by hand.
+    #[serde(default)]
+    pub(crate) manual_planned_value: Option<bool>,
+    /// Set by hand, so not derived from the logged hours and the rates of the members. Missing
+    /// in files saved before costs could be derived, where any cost was set by hand.
+    #[serde(default)]
+    pub(crate) manual_actual_cost: Option<bool>,
 }
 
 impl Eq for Task {}
@@ -67,6 +145,18 @@ impl Task {
             status: TaskStatus::InProgress,
             dependencies: HashSet::new(),
             dependency_for: HashSet::new(),
+            dependency_links: HashMap::new(),
+            target_date: None,
+            due_date: None,
+            estimate: None,
+            priority: 0,
+            expected_effort: 0.0,
+            effort_variance: 0.0,
+            planned_dates: None,
+            required_skills: BTreeMap::new(),
+            work_logs: Vec::new(),
+            manual_planned_value: Some(false),
+            manual_actual_cost: Some(false),
         }
     }
 
@@ -86,6 +176,64 @@ impl Task {
         self.actual_cost
     }
 
+    pub fn work_logs(&self) -> &[WorkLog] {
+        &self.work_logs
+    }
+
+    pub fn logged_hours(&self) -> f64 {
+        self.work_logs.iter().fold(0.0, |total, log| total + log.hours)
+    }
+
+    pub fn has_manual_planned_value(&self) -> bool {
+        self.manual_planned_value.unwrap_or(self.planned_value != 0.0)
+    }
+
+    pub fn has_manual_actual_cost(&self) -> bool {
+        self.manual_actual_cost.unwrap_or(self.actual_cost != 0.0)
+    }
+
+    pub fn required_skills(&self) -> &BTreeMap<String, Proficiency> {
+        &self.required_skills
+    }
+
+    /// First and last day of work, as set by the last resource leveling
+    pub fn planned_dates(&self) -> Option<(NaiveDate, NaiveDate)> {
+        self.planned_dates
+    }
+
+    /// Type and lag of the edge from this task to `dependency_id`
+    pub fn dependency_link(&self, dependency_id: &TaskId) -> DependencyLink {
+        self.dependency_links
+            .get(dependency_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn estimate(&self) -> Option<&Estimate> {
+        self.estimate.as_ref()
+    }
+
+    /// Expected effort in hours. For trunks, this is the sum of the expected effort of their
+    /// children.
+    pub fn expected_effort(&self) -> f64 {
+        self.expected_effort
+    }
+
+    /// Standard deviation of the effort in hours, assuming children are independent
+    pub fn effort_std_dev(&self) -> f64 {
+        self.effort_variance.sqrt()
+    }
+
+    /// Remaining duration in days, from the expected effort. Done tasks, milestones and tasks
+    /// without an estimate take no time.
+    pub fn remaining_duration(&self) -> f64 {
+        if self.status == TaskStatus::Done {
+            0.0
+        } else {
+            self.expected_effort / HOURS_PER_DAY
+        }
+    }
+
     pub fn child_ids(&self) -> impl Iterator<Item=TaskId> + '_ {
         self.id().child_ids(self.num_child)
     }
@@ -98,10 +246,49 @@ impl Task {
         self.num_child == 0
     }
 
+    pub fn is_milestone(&self) -> bool {
+        self.target_date.is_some()
+    }
+
+    pub fn target_date(&self) -> Option<&NaiveDate> {
+        self.target_date.as_ref()
+    }
+
+    pub fn due_date(&self) -> Option<&NaiveDate> {
+        self.due_date.as_ref()
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Milestones are reached once all of their dependencies are done. Returns `None` if this
+    /// task isn't a milestone.
+    pub fn milestone_status(&self, today: &NaiveDate) -> Option<MilestoneStatus> {
+        let target_date = self.target_date()?;
+        Some(if self.status == TaskStatus::Done {
+            MilestoneStatus::Reached
+        } else if today > target_date {
+            MilestoneStatus::Missed
+        } else if (*target_date - *today).num_days() <= MILESTONE_AT_RISK_DAYS {
+            MilestoneStatus::AtRisk
+        } else {
+            MilestoneStatus::OnTrack
+        })
+    }
+
     pub fn to_dot_str(&self) -> String {
+        if let Some(target_date) = self.target_date() {
+            return format!(
+                "{} - {} {}\ntarget: {}",
+                self.id(),
+                self.name(),
+                self.status.to_icon(),
+                target_date)
+        }
         format!(
             "{} - {} {}\npv: {} ac: {}",
-            self.id().to_string(),
+            self.id(),
             self.name(),
             self.status.to_icon(),
             self.get_planned_value(),
@@ -113,9 +300,14 @@ impl Display for Task {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let dependencies = self.dependencies.iter().fold(String::new(), |acc, id| acc + &id.to_string() + " ");
         let dependencies = dependencies.trim_end();
+        let dates = match (self.target_date(), self.due_date()) {
+            (Some(target_date), _) => format!(" (milestone {})", target_date),
+            (None, Some(due_date)) => format!(" (due {})", due_date),
+            (None, None) => String::new()
+        };
         match self.id().as_vec().last() {
-            Some(_) => write!(f, "{} - {} {} -> [{}]", self.id().to_string(), self.name(), self.status.to_icon(), dependencies),
-            None => write!(f, "{} {}", self.name().to_string(), self.status.to_icon()),
+            Some(_) => write!(f, "{} - {} {}{} -> [{}]", self.id(), self.name(), self.status.to_icon(), dates, dependencies),
+            None => write!(f, "{} {}", self.name(), self.status.to_icon()),
         }
     }
 }
@@ -130,4 +322,17 @@ mod tests {
         let task = Task::new(task_id.clone(), "Create Task Struct");
         assert_eq!(task.id(), &task_id);
     }
+
+    #[test]
+    fn milestone_status() {
+        let mut task = Task::new(TaskId::parse("1").unwrap(), "Release");
+        let today = NaiveDate::from_ymd(2022, 10, 10);
+        assert_eq!(task.milestone_status(&today), None);
+        task.target_date = Some(NaiveDate::from_ymd(2022, 11, 10));
+        assert_eq!(task.milestone_status(&today), Some(MilestoneStatus::OnTrack));
+        assert_eq!(task.milestone_status(&NaiveDate::from_ymd(2022, 11, 5)), Some(MilestoneStatus::AtRisk));
+        assert_eq!(task.milestone_status(&NaiveDate::from_ymd(2022, 11, 11)), Some(MilestoneStatus::Missed));
+        task.status = TaskStatus::Done;
+        assert_eq!(task.milestone_status(&NaiveDate::from_ymd(2022, 11, 11)), Some(MilestoneStatus::Reached));
+    }
 }
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use crate::prelude::{TaskId, Tasks, Error};
+
+use super::dependency::{DependencyLink, DependencyType};
+
+impl Tasks {
+
+    /// Dependency edges, as (task, dependency), that `reduce_dependencies` would remove
+    pub fn redundant_dependencies(&self) -> Result<Vec<(TaskId, TaskId)>, Error> {
+        self.clone().reduce_dependencies()
+    }
+
+    /// Transitive reduction: remove every dependency edge that is implied by the others, in id
+    /// order, returning the removed edges as (task, dependency). Edges are removed one at a
+    /// time, so two edges implying each other don't both go.
+    ///
+    /// Only plain finish-to-start edges are removed, and only chains of finish-to-start edges
+    /// without negative lags imply them: other chains don't guarantee that the dependency is
+    /// finished when the task starts.
+    pub(crate) fn reduce_dependencies(&mut self) -> Result<Vec<(TaskId, TaskId)>, Error> {
+        let edges = self.sorted_ids()
+            .into_iter()
+            .map(|id| self.get(id).unwrap())
+            .flat_map(|task| {
+                let mut dependencies = task.dependencies
+                    .iter()
+                    .filter(|dependency_id| task.dependency_link(dependency_id) == DependencyLink::default())
+                    .collect::<Vec<_>>();
+                dependencies.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+                dependencies.into_iter().map(|dependency_id| (task.id().clone(), dependency_id.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let mut removed = vec![];
+        for (task_id, dependency_id) in edges {
+            if self.is_implied(&task_id, &dependency_id) {
+                self.remove_dependency(&task_id, &dependency_id)?;
+                removed.push((task_id, dependency_id));
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Whether every leaf under `dependency_id` still comes before every leaf under `task_id`
+    /// without the edge between them
+    fn is_implied(&self, task_id: &TaskId, dependency_id: &TaskId) -> bool {
+        let required = self.leaves_under(dependency_id);
+        self.leaves_under(task_id)
+            .into_iter()
+            .all(|leaf_id| {
+                let reachable = self.reachable_dependencies(leaf_id, (task_id, dependency_id));
+                required.iter().all(|id| reachable.contains(id))
+            })
+    }
+
+    /// Leaves `leaf_id` has to wait for, through finish-to-start edges without negative lags
+    /// other than the stored edge `skipped`
+    fn reachable_dependencies<'a>(&'a self, leaf_id: &'a TaskId, skipped: (&TaskId, &TaskId)) -> HashSet<&'a TaskId> {
+        let mut reachable = HashSet::new();
+        let mut stack = vec![leaf_id];
+        while let Some(id) = stack.pop() {
+            for (dependency_id, link) in self.leaf_dependencies_except(id, Some(skipped)) {
+                if link.kind != DependencyType::FinishToStart || link.lag < 0 {
+                    continue
+                }
+                if reachable.insert(dependency_id) {
+                    stack.push(dependency_id);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitive_reduction() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Backend"),
+                ("1", "Database"),
+                ("1", "API"),
+            ("", "Frontend"),
+            ("", "Release"),
+        ]).unwrap();
+        let task_id_1 = TaskId::new(vec![1]);
+        let task_id_1_1 = TaskId::new(vec![1, 1]);
+        let task_id_1_2 = TaskId::new(vec![1, 2]);
+        let task_id_2 = TaskId::new(vec![2]);
+        let task_id_3 = TaskId::new(vec![3]);
+        tasks.add_dependency(&task_id_1_2, &task_id_1_1).unwrap();
+        tasks.add_dependency(&task_id_2, &task_id_1).unwrap();
+        tasks.add_dependency(&task_id_3, &task_id_2).unwrap();
+        // implied by 3 -> 2 -> 1
+        tasks.add_dependency(&task_id_3, &task_id_1_2).unwrap();
+        // implied too, but the lag makes it stricter than the chain
+        tasks.add_dependency_with(&task_id_3, &task_id_1_1, DependencyLink::new(DependencyType::FinishToStart, 5)).unwrap();
+        // the start-to-start edge doesn't imply that 1.2 is done when 4 starts
+        let task_id_4 = tasks.add_task(TaskId::get_root_id(), "Demo").unwrap().id().clone();
+        let task_id_5 = tasks.add_task(TaskId::get_root_id(), "Feedback").unwrap().id().clone();
+        tasks.add_dependency_with(&task_id_4, &task_id_1_2, DependencyLink::new(DependencyType::StartToStart, 0)).unwrap();
+        tasks.add_dependency(&task_id_5, &task_id_4).unwrap();
+        tasks.add_dependency(&task_id_5, &task_id_1_2).unwrap();
+        // implied by 2 -> 1, which holds for every leaf under 1
+        tasks.add_dependency(&task_id_2, &task_id_1_1).unwrap();
+
+        let redundant = vec![(task_id_2.clone(), task_id_1_1.clone()), (task_id_3.clone(), task_id_1_2.clone())];
+        assert_eq!(tasks.redundant_dependencies().as_ref(), Ok(&redundant));
+        assert_eq!(tasks.get(&task_id_3).unwrap().dependencies.len(), 3);
+
+        assert_eq!(tasks.reduce_dependencies(), Ok(redundant));
+        assert_eq!(tasks.redundant_dependencies(), Ok(vec![]));
+        assert!(tasks.get(&task_id_3).unwrap().dependencies.contains(&task_id_1_1));
+        assert!(tasks.validate().is_empty());
+    }
+}
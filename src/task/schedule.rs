@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::prelude::{TaskId, Tasks};
+
+/// Tolerance used when deciding whether a task has no slack
+pub(crate) const SLACK_EPSILON: f64 = 1e-9;
+
+/// Earliest and latest start and finish of a leaf task, in days since the project start
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TaskTimes {
+    pub earliest_start: f64,
+    pub earliest_finish: f64,
+    pub latest_start: f64,
+    pub latest_finish: f64,
+    /// How much the task can slip without delaying any of its dependents
+    pub free_slack: f64,
+}
+
+impl TaskTimes {
+    /// How much the task can slip without delaying the project
+    pub fn total_slack(&self) -> f64 {
+        self.latest_start - self.earliest_start
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.total_slack() <= SLACK_EPSILON
+    }
+}
+
+/// Result of the critical path method over the leaves of a project
+#[derive(Clone, Debug, PartialEq)]
+pub struct CriticalPath {
+    /// Remaining duration of the project, in days
+    pub duration: f64,
+    /// Critical leaves, in dependency order
+    pub tasks: Vec<TaskId>,
+    times: HashMap<TaskId, TaskTimes>,
+    /// Dependency edges, as (task, dependency), that drive the earliest start of their task
+    driving_edges: HashSet<(TaskId, TaskId)>,
+}
+
+impl CriticalPath {
+    pub fn times(&self, id: &TaskId) -> Option<&TaskTimes> {
+        self.times.get(id)
+    }
+
+    pub fn is_critical(&self, id: &TaskId) -> bool {
+        self.times(id).is_some_and(|t| t.is_critical())
+    }
+
+    /// Whether `id` depending on `dependency` is an edge of the critical path: both are critical
+    /// and the edge, with its type and lag, is what sets the earliest start of the task
+    pub fn is_critical_edge(&self, id: &TaskId, dependency: &TaskId) -> bool {
+        self.is_critical(id)
+            && self.is_critical(dependency)
+            && self.driving_edges.contains(&(id.clone(), dependency.clone()))
+    }
+}
+
+impl Tasks {
+
+    pub fn critical_path(&self) -> CriticalPath {
+        let order = self.topological_order();
+        let durations = order
+            .iter()
+            .map(|id| (*id, self.get(id).unwrap().remaining_duration()))
+            .collect::<HashMap<_, _>>();
+        let times = self.schedule_passes(&order, &durations);
+        let driving_edges = order
+            .iter()
+            .flat_map(|id| {
+                self.leaf_dependencies(id)
+                    .into_iter()
+                    .filter(|(dependency_id, link)| {
+                        let dependency = &times[dependency_id];
+                        let start = link.earliest_start(dependency.earliest_start, dependency.earliest_finish, durations[id]);
+                        (times[id].earliest_start - start).abs() <= SLACK_EPSILON
+                    })
+                    .map(|(dependency_id, _)| ((*id).clone(), dependency_id.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        CriticalPath {
+            duration: times.values().map(|t| t.earliest_finish).fold(0.0, f64::max),
+            tasks: order.iter().filter(|id| times[*id].is_critical()).map(|id| (*id).clone()).collect(),
+            times: times.into_iter().map(|(id, t)| (id.clone(), t)).collect(),
+            driving_edges,
+        }
+    }
+
+    /// Leaf ids ordered so that every task comes after its dependencies. Ties are broken by id,
+    /// so the order is always the same for the same project.
+    pub(crate) fn topological_order(&self) -> Vec<&TaskId> {
+        let mut leaves = self.get_tasks().collect::<Vec<_>>();
+        leaves.sort_by(|a, b| a.id().as_vec().cmp(b.id().as_vec()));
+
+        // trunk edges expand to many leaf edges, possibly repeating one, so count each once
+        let mut dependents : HashMap<&TaskId, Vec<&TaskId>> = HashMap::new();
+        let mut missing = leaves
+            .iter()
+            .map(|task| {
+                let dependencies = self.leaf_dependencies(task.id())
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect::<HashSet<_>>();
+                dependencies
+                    .iter()
+                    .for_each(|dependency_id| dependents.entry(dependency_id).or_default().push(task.id()));
+                (task.id(), dependencies.len())
+            })
+            .collect::<HashMap<_, _>>();
+        let mut ready = leaves
+            .iter()
+            .filter(|task| missing[task.id()] == 0)
+            .map(|task| task.id())
+            .collect::<Vec<_>>();
+        ready.reverse();
+
+        let mut order = Vec::with_capacity(leaves.len());
+        while let Some(id) = ready.pop() {
+            order.push(id);
+            let mut dependents = dependents.remove(id).unwrap_or_default();
+            dependents.sort_by(|a, b| b.as_vec().cmp(a.as_vec()));
+            for dependent in dependents {
+                let count = missing.get_mut(dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+        order
+    }
+
+    /// Forward and backward passes over the dependency graph, given the duration in days of
+    /// each leaf in `order`. Edges are applied with their type and lag, and no task starts
+    /// before the project does.
+    pub(crate) fn schedule_passes<'a>(&self, order: &[&'a TaskId], durations: &HashMap<&TaskId, f64>) -> HashMap<&'a TaskId, TaskTimes> {
+        let mut times : HashMap<&TaskId, TaskTimes> = HashMap::with_capacity(order.len());
+        for id in order {
+            let earliest_start = self.leaf_dependencies(id)
+                .into_iter()
+                .map(|(dependency_id, link)| {
+                    let dependency = &times[dependency_id];
+                    link.earliest_start(dependency.earliest_start, dependency.earliest_finish, durations[id])
+                })
+                .fold(0.0, f64::max);
+            let earliest_finish = earliest_start + durations[id];
+            times.insert(id, TaskTimes { earliest_start, earliest_finish, latest_start: 0.0, latest_finish: 0.0, free_slack: 0.0 });
+        }
+
+        let project_finish = times.values().map(|t| t.earliest_finish).fold(0.0, f64::max);
+        for id in order.iter().rev() {
+            let dependents = self.leaf_dependents(id);
+            let latest_finish = dependents
+                .iter()
+                .map(|(dependent_id, link)| {
+                    let dependent = &times[dependent_id];
+                    link.latest_finish(dependent.latest_start, dependent.latest_finish, durations[id])
+                })
+                .fold(project_finish, f64::min);
+            // latest finish that keeps every dependent at its earliest times
+            let free_finish = dependents
+                .iter()
+                .map(|(dependent_id, link)| {
+                    let dependent = &times[dependent_id];
+                    link.latest_finish(dependent.earliest_start, dependent.earliest_finish, durations[id])
+                })
+                .fold(project_finish, f64::min);
+            let task_times = times.get_mut(id).unwrap();
+            task_times.latest_finish = latest_finish;
+            task_times.latest_start = latest_finish - durations[id];
+            task_times.free_slack = free_finish - task_times.earliest_finish;
+        }
+
+        order.iter().map(|id| (*id, times[id])).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Estimate, DependencyLink, DependencyType};
+
+    #[test]
+    fn critical_path() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Design"),
+            ("", "Backend"),
+            ("", "Frontend"),
+            ("", "Release"),
+        ]).unwrap();
+        let design = TaskId::new(vec![1]);
+        let backend = TaskId::new(vec![2]);
+        let frontend = TaskId::new(vec![3]);
+        let release = TaskId::new(vec![4]);
+        for (id, hours) in [(&design, 16.0), (&backend, 40.0), (&frontend, 24.0), (&release, 8.0)] {
+            tasks.set_estimate(id, Some(Estimate::new(hours).unwrap())).unwrap();
+        }
+        tasks.add_dependency(&backend, &design).unwrap();
+        tasks.add_dependency(&frontend, &design).unwrap();
+        tasks.add_dependency(&release, &backend).unwrap();
+        tasks.add_dependency(&release, &frontend).unwrap();
+
+        let critical_path = tasks.critical_path();
+        assert_eq!(critical_path.duration, 8.0);
+        assert_eq!(critical_path.tasks, vec![design.clone(), backend.clone(), release.clone()]);
+        let frontend_times = critical_path.times(&frontend).unwrap();
+        assert_eq!(frontend_times.earliest_start, 2.0);
+        assert_eq!(frontend_times.latest_start, 4.0);
+        assert_eq!(frontend_times.total_slack(), 2.0);
+        assert_eq!(frontend_times.free_slack, 2.0);
+        assert!(critical_path.is_critical_edge(&release, &backend));
+        assert!(!critical_path.is_critical_edge(&release, &frontend));
+        assert!(!critical_path.is_critical_edge(&frontend, &design));
+    }
+
+    #[test]
+    fn dependency_types_and_lags() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Pour concrete"),
+            ("", "Lay bricks"),
+            ("", "Inspection"),
+        ]).unwrap();
+        let concrete = TaskId::new(vec![1]);
+        let bricks = TaskId::new(vec![2]);
+        let inspection = TaskId::new(vec![3]);
+        for (id, hours) in [(&concrete, 32.0), (&bricks, 16.0), (&inspection, 8.0)] {
+            tasks.set_estimate(id, Some(Estimate::new(hours).unwrap())).unwrap();
+        }
+        // bricks can start two days after the concrete starts, the inspection ends a day after
+        // the bricks are done
+        tasks.add_dependency_with(&bricks, &concrete, DependencyLink::new(DependencyType::StartToStart, 2)).unwrap();
+        tasks.add_dependency_with(&inspection, &bricks, DependencyLink::new(DependencyType::FinishToFinish, 1)).unwrap();
+
+        let critical_path = tasks.critical_path();
+        assert_eq!(critical_path.duration, 5.0);
+        assert_eq!(critical_path.times(&bricks).unwrap().earliest_start, 2.0);
+        assert_eq!(critical_path.times(&inspection).unwrap().earliest_start, 4.0);
+        assert!(critical_path.is_critical_edge(&inspection, &bricks));
+        assert_eq!(critical_path.times(&concrete).unwrap().total_slack(), 0.0);
+        assert!(critical_path.is_critical_edge(&bricks, &concrete));
+
+        tasks.set_dependency_link(&bricks, &concrete, DependencyLink::default()).unwrap();
+        assert_eq!(tasks.get(&bricks).unwrap().dependency_link(&concrete), DependencyLink::default());
+        assert_eq!(tasks.critical_path().duration, 7.0);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::prelude::{TaskId, Tasks, Estimate, Calendar};
+
+use super::{TaskStatus, estimate::HOURS_PER_DAY};
+
+/// Small deterministic generator (SplitMix64), so simulations can be reproduced from a seed
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform sample in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Sample from the triangular distribution described by a three-point estimate
+    fn triangular(&mut self, estimate: &Estimate) -> f64 {
+        let (a, m, b) = (estimate.optimistic(), estimate.most_likely(), estimate.pessimistic());
+        if b - a <= 0.0 {
+            return m
+        }
+        let u = self.next_f64();
+        if u < (m - a) / (b - a) {
+            a + (u * (b - a) * (m - a)).sqrt()
+        } else {
+            b - ((1.0 - u) * (b - a) * (b - m)).sqrt()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulationOptions {
+    /// Date the remaining work starts
+    pub start: NaiveDate,
+    pub iterations: usize,
+    pub seed: u64,
+    /// Used for unfinished leaves that have no estimate
+    pub default_estimate: Estimate,
+    /// Working days the remaining work is spread over
+    pub calendar: Calendar,
+}
+
+impl SimulationOptions {
+    pub fn new(start: NaiveDate) -> Self {
+        Self {
+            start,
+            iterations: 10_000,
+            seed: 0,
+            // SAFETY: these values are in the right order
+            default_estimate: Estimate::three_point(4.0, 8.0, 16.0).unwrap(),
+            calendar: Calendar::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    pub iterations: usize,
+    pub p50: NaiveDate,
+    pub p80: NaiveDate,
+    pub p95: NaiveDate,
+    /// Fraction of the iterations in which each leaf was on the critical path, highest first
+    pub criticality: Vec<(TaskId, f64)>,
+}
+
+impl Tasks {
+
+    /// Monte Carlo simulation of the remaining work. Leaf durations are sampled from their
+    /// estimates (in working hours, `HOURS_PER_DAY` per working day of the calendar) and
+    /// propagated through the dependency graph. Done tasks and milestones take no time.
+    pub fn simulate(&self, options: &SimulationOptions) -> SimulationReport {
+        let mut rng = SplitMix64(options.seed);
+        let order = self.topological_order();
+        let mut durations : HashMap<&TaskId, f64> = HashMap::with_capacity(order.len());
+        let mut finishes = Vec::with_capacity(options.iterations);
+        let mut critical_count : HashMap<&TaskId, usize> = HashMap::new();
+
+        for _ in 0..options.iterations {
+            // sampling follows `order`, so the same seed always gives the same result
+            for id in &order {
+                let task = self.get(id).unwrap();
+                let hours = if task.status == TaskStatus::Done || task.is_milestone() {
+                    0.0
+                } else {
+                    rng.triangular(task.estimate().unwrap_or(&options.default_estimate))
+                };
+                durations.insert(id, hours / HOURS_PER_DAY);
+            }
+            let times = self.schedule_passes(&order, &durations);
+            finishes.push(times.values().map(|t| t.earliest_finish).fold(0.0, f64::max));
+            times.iter()
+                .filter(|(_, t)| t.is_critical())
+                .for_each(|(id, _)| *critical_count.entry(id).or_insert(0) += 1);
+        }
+
+        finishes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let percentile = |p: f64| {
+            let days = finishes
+                .get(((finishes.len() as f64 * p).ceil() as usize).saturating_sub(1))
+                .cloned()
+                .unwrap_or(0.0);
+            options.calendar.finish_date(options.start, days.ceil() as u32)
+        };
+
+        let mut criticality = critical_count
+            .into_iter()
+            .map(|(id, count)| (id.clone(), count as f64 / options.iterations as f64))
+            .collect::<Vec<_>>();
+        criticality.sort_by(|(a_id, a), (b_id, b)| b.partial_cmp(a).unwrap().then(a_id.as_vec().cmp(b_id.as_vec())));
+
+        SimulationReport {
+            iterations: options.iterations,
+            p50: percentile(0.5),
+            p80: percentile(0.8),
+            p95: percentile(0.95),
+            criticality,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_is_reproducible() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Design"),
+            ("", "Build"),
+            ("", "Docs"),
+        ]).unwrap();
+        let design = TaskId::new(vec![1]);
+        let build = TaskId::new(vec![2]);
+        let docs = TaskId::new(vec![3]);
+        tasks.set_estimate(&design, Some(Estimate::three_point(8.0, 16.0, 40.0).unwrap())).unwrap();
+        tasks.set_estimate(&build, Some(Estimate::three_point(16.0, 24.0, 64.0).unwrap())).unwrap();
+        tasks.set_estimate(&docs, Some(Estimate::new(4.0).unwrap())).unwrap();
+        tasks.add_dependency(&build, &design).unwrap();
+
+        let start = NaiveDate::from_ymd(2022, 10, 3);
+        let mut options = SimulationOptions::new(start);
+        options.iterations = 2000;
+        options.seed = 7;
+        let report = tasks.simulate(&options);
+        assert_eq!(report, tasks.simulate(&options));
+        assert!(report.p50 <= report.p80 && report.p80 <= report.p95);
+        // design + build take at least 3 working days and at most 13, from a Monday
+        assert!(report.p50 >= NaiveDate::from_ymd(2022, 10, 5) && report.p95 <= NaiveDate::from_ymd(2022, 10, 19));
+
+        let criticality = report.criticality.iter().cloned().collect::<HashMap<_, _>>();
+        assert_eq!(criticality[&design], 1.0);
+        assert_eq!(criticality[&build], 1.0);
+        assert_eq!(criticality.get(&docs), None);
+    }
+
+    #[test]
+    fn simulation_skips_days_off() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[("", "Design")]).unwrap();
+        tasks.set_estimate(&TaskId::new(vec![1]), Some(Estimate::new(24.0).unwrap())).unwrap();
+
+        // three days of work from a Friday, with the Monday off
+        let mut options = SimulationOptions::new(NaiveDate::from_ymd(2022, 10, 7));
+        options.iterations = 10;
+        options.calendar.add_holiday(NaiveDate::from_ymd(2022, 10, 10));
+        let report = tasks.simulate(&options);
+        assert_eq!((report.p50, report.p95), (NaiveDate::from_ymd(2022, 10, 12), NaiveDate::from_ymd(2022, 10, 12)));
+
+        // nothing left to do ends on the start
+        tasks.set_actual_cost(&TaskId::new(vec![1]), 1.0).unwrap();
+        assert_eq!(tasks.simulate(&options).p50, NaiveDate::from_ymd(2022, 10, 7));
+    }
+}
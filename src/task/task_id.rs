@@ -36,8 +36,8 @@ impl TaskId {
         self.id.len()
     }
 
-    pub fn into_iter(self) -> std::vec::IntoIter<u32> {
-        self.id.into_iter()
+    pub fn is_empty(&self) -> bool {
+        self.id.is_empty()
     }
 
     pub fn child_idx(&self) -> Result<u32, Error> {
@@ -55,7 +55,7 @@ impl TaskId {
             .split(".")
             .map(|n| n.parse::<u32>())
             .collect::<Result<Vec<u32>, _>>()
-            .or_else(|_| Err(Error::BadTaskIdString(id.to_string())))?;
+            .map_err(|_| Error::BadTaskIdString(id.to_string()))?;
         Ok(TaskId::new(vec))
     }
 
@@ -87,10 +87,10 @@ impl TaskId {
             let mut vec = id_vec.clone();
             vec.push(child_index);
             TaskId::new(vec)
-        }).into_iter()
+        })
     }
 
-    pub fn path(&self) -> impl Iterator<Item=TaskId> + DoubleEndedIterator + '_ {
+    pub fn path(&self) -> impl DoubleEndedIterator<Item=TaskId> + '_ {
 
         let id_iter = self.id
             .iter()
@@ -102,7 +102,6 @@ impl TaskId {
             });
         std::iter::once(Self::get_root_id())
             .chain(id_iter)
-            .into_iter()
     }
 
     pub fn new_child_id(&self, child_num: u32) -> Result<TaskId, Error> {
@@ -133,6 +132,15 @@ impl Display for TaskId {
     }
 }
 
+impl IntoIterator for TaskId {
+    type Item = u32;
+    type IntoIter = std::vec::IntoIter<u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.id.into_iter()
+    }
+}
+
 impl FromIterator<u32> for TaskId {
     fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
         TaskId::new(iter.into_iter().collect::<Vec<u32>>())
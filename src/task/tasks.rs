@@ -1,11 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, VecDeque};
+
+use chrono::NaiveDate;
 
 use serde::{Serialize, Deserialize};
 use serde_with::serde_as;
 
-use crate::prelude::{TaskId, Error, Members};
+use crate::prelude::{TaskId, Error, Members, Violation, Proficiency};
+
+use super::{Task, TaskStatus, SplitAmounts, estimate::Estimate, schedule::CriticalPath, dependency::DependencyLink, work_log::WorkLog};
 
-use super::{Task, TaskStatus};
+/// Tolerance used when checking that values add up to the value of their parent
+const EPSILON: f64 = 1e-9;
 
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,10 +19,29 @@ pub struct Tasks {
     store: HashMap<TaskId, Task>
 }
 
-enum DFSVertexStatus {
-    Done,
-    InStack,
-    NotVisited
+/// What to show besides the task hierarchy when rendering it
+#[derive(Clone, Debug, Default)]
+pub struct ViewOptions {
+    /// Show effort estimates, flagging leaves without one
+    pub estimates: bool,
+    /// Highlight the critical path and show the slack of the other leaves
+    pub critical_path: bool,
+    /// Show the dates planned by the last resource leveling
+    pub schedule: bool,
+    /// Owning team of each task, to fill the tasks with the color of their team in DOT output.
+    /// Empty to leave them unfilled.
+    pub teams: HashMap<TaskId, String>,
+}
+
+/// Fill colors given to teams in DOT output, in the order of their names
+const TEAM_COLORS: [&str; 8] = ["lightblue", "palegreen", "khaki", "lightpink", "plum", "lightsalmon", "paleturquoise", "wheat"];
+
+/// View options, along with what has to be computed beforehand to honor them
+struct View<'a> {
+    options: &'a ViewOptions,
+    critical_path: Option<CriticalPath>,
+    /// Fill color of each team in `options.teams`
+    team_colors: BTreeMap<&'a str, &'static str>,
 }
 
 impl Tasks {
@@ -49,6 +73,12 @@ impl Tasks {
         self.get(&TaskId::get_root_id()).unwrap().get_actual_cost()
     }
 
+    /// SAFETY: uses `unwrap` instead of returning an error because a root node should always
+    /// exists
+    pub fn expected_effort(&self) -> f64 {
+        self.get(&TaskId::get_root_id()).unwrap().expected_effort()
+    }
+
     pub fn completion_percentage(&self) -> f64 {
         self.get_done_tasks().count() as f64 / self.len() as f64
     }
@@ -89,23 +119,47 @@ impl Tasks {
     }
 
     pub(crate) fn add_dependency(&mut self, task_id: &TaskId, dependency_id: &TaskId) -> Result<(), Error> {
-        if self.get_mut(task_id)?.is_trunk() {
-            return Err(Error::TrunkCannotHaveDependency(task_id.clone()))
-        }
-        if self.get_mut(dependency_id)?.is_trunk() {
-            return Err(Error::TrunkCannotBeDependency(dependency_id.clone()))
+        self.add_dependency_with(task_id, dependency_id, DependencyLink::default())
+    }
+
+    /// Add a dependency edge of any type. Cycles are rejected whatever the types and lags of
+    /// the edges in them, since scheduling follows `dependencies` in order.
+    ///
+    /// Either end can be a trunk, meaning that every leaf under `dependency_id` comes before
+    /// any leaf under `task_id`. Edges stay on the trunk, so leaves added to it later follow
+    /// them too.
+    pub(crate) fn add_dependency_with(&mut self, task_id: &TaskId, dependency_id: &TaskId, link: DependencyLink) -> Result<(), Error> {
+        self.get(task_id)?;
+        self.get(dependency_id)?;
+        if let Some(cycle) = self.dependency_cycle(task_id, dependency_id) {
+            return Err(Error::EdgeCreationLeadsToCycle(task_id.clone(), dependency_id.clone(), cycle))
         }
-        // SAFETY: we already performed `get_mut`, so we know these exist
+        // SAFETY: we already performed `get`, so we know these exist
         self.store.get_mut(dependency_id).unwrap().dependency_for.insert(task_id.clone());
         self.store.get_mut(task_id).unwrap().dependencies.insert(dependency_id.clone());
-        if self.dependency_cycle_exists()? {
-            self.store.get_mut(task_id).unwrap().dependencies.remove(dependency_id);
-            self.store.get_mut(dependency_id).unwrap().dependency_for.remove(task_id);
-            return Err(Error::EdgeCreationLeadsToCycle(task_id.clone(), dependency_id.clone()))
+        self.set_link(task_id, dependency_id, link);
+        self.refresh_milestones_under(task_id)
+    }
+
+    /// Change the type and lag of an existing dependency edge
+    pub(crate) fn set_dependency_link(&mut self, task_id: &TaskId, dependency_id: &TaskId, link: DependencyLink) -> Result<(), Error> {
+        if !self.get(task_id)?.dependencies.contains(dependency_id) {
+            return Err(Error::TaskNotFound(dependency_id.clone()));
         }
+        self.set_link(task_id, dependency_id, link);
         Ok(())
     }
 
+    /// Only edges that aren't finish-to-start without lag are stored
+    fn set_link(&mut self, task_id: &TaskId, dependency_id: &TaskId, link: DependencyLink) {
+        let task = self.store.get_mut(task_id).unwrap();
+        if link == DependencyLink::default() {
+            task.dependency_links.remove(dependency_id);
+        } else {
+            task.dependency_links.insert(dependency_id.clone(), link);
+        }
+    }
+
     pub(crate) fn remove_dependency(&mut self, task_id: &TaskId, dependency_id: &TaskId) -> Result<(), Error> {
         if !self.get_mut(task_id)?.dependencies.contains(dependency_id) {
             return Err(Error::TaskNotFound(dependency_id.clone()));
@@ -115,11 +169,123 @@ impl Tasks {
         }
         // SAFETY: we already performed `get_mut`, so we know these exist
         self.get_mut(task_id).unwrap().dependencies.remove(dependency_id);
+        self.get_mut(task_id).unwrap().dependency_links.remove(dependency_id);
         self.get_mut(dependency_id).unwrap().dependency_for.remove(task_id);
 
+        self.refresh_milestones_under(task_id)
+    }
+
+    /// Leaves in the subtree of `task_id`, which is its own only leaf if it is one
+    pub(crate) fn leaves_under(&self, task_id: &TaskId) -> Vec<&TaskId> {
+        let mut leaves = vec![];
+        let mut stack = vec![task_id.clone()];
+        while let Some(id) = stack.pop() {
+            let Some(task) = self.store.get(&id) else { continue };
+            if task.is_leaf() {
+                leaves.push(task.id());
+            } else {
+                stack.extend(task.child_ids());
+            }
+        }
+        leaves
+    }
+
+    /// Dependencies of the leaf `task_id` as seen by scheduling: edges of the leaf and of its
+    /// ancestors, with trunks on the other end replaced by their leaves. A leaf can show up
+    /// more than once, through different edges.
+    pub(crate) fn leaf_dependencies(&self, task_id: &TaskId) -> Vec<(&TaskId, DependencyLink)> {
+        self.leaf_dependencies_except(task_id, None)
+    }
+
+    /// Same as `leaf_dependencies`, ignoring the stored edge `skipped`, given as
+    /// (task, dependency)
+    pub(crate) fn leaf_dependencies_except(&self, task_id: &TaskId, skipped: Option<(&TaskId, &TaskId)>) -> Vec<(&TaskId, DependencyLink)> {
+        task_id
+            .path()
+            .filter_map(|id| self.store.get(&id))
+            .flat_map(|task| task.dependencies
+                .iter()
+                .filter(move |dependency_id| skipped != Some((task.id(), *dependency_id)))
+                .flat_map(move |dependency_id| self.leaves_under(dependency_id)
+                    .into_iter()
+                    .map(move |leaf_id| (leaf_id, task.dependency_link(dependency_id)))))
+            .collect()
+    }
+
+    /// Dependents of the leaf `task_id` as seen by scheduling, the reverse of
+    /// `leaf_dependencies`
+    pub(crate) fn leaf_dependents(&self, task_id: &TaskId) -> Vec<(&TaskId, DependencyLink)> {
+        task_id
+            .path()
+            .filter_map(|id| self.store.get(&id))
+            .flat_map(|task| task.dependency_for
+                .iter()
+                .filter_map(|dependent_id| self.store.get(dependent_id))
+                .flat_map(move |dependent| self.leaves_under(dependent.id())
+                    .into_iter()
+                    .map(move |leaf_id| (leaf_id, dependent.dependency_link(task.id())))))
+            .collect()
+    }
+
+    /// Recompute the status of the milestones under `task_id`, whose dependencies may have
+    /// changed
+    fn refresh_milestones_under(&mut self, task_id: &TaskId) -> Result<(), Error> {
+        self.leaves_under(task_id)
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .iter()
+            .try_for_each(|id| self.refresh_milestone(id))
+    }
+
+    /// Create a milestone: a zero-effort leaf that is reached once all of its dependencies are done
+    pub(crate) fn add_milestone(&mut self, parent_task_id: TaskId, name: &str, target_date: NaiveDate) -> Result<&mut Task, Error> {
+        let task_id = self.add_task(parent_task_id, name)?.id.clone();
+        self.get_mut(&task_id)?.target_date = Some(target_date);
+        self.get_mut(&task_id)
+    }
+
+    pub(crate) fn set_target_date(&mut self, task_id: &TaskId, target_date: NaiveDate) -> Result<(), Error> {
+        let task = self.get_mut(task_id)?;
+        if !task.is_milestone() {
+            return Err(Error::NotAMilestone(task_id.clone()))
+        }
+        task.target_date = Some(target_date);
         Ok(())
     }
 
+    pub fn get_milestones(&self) -> impl Iterator<Item=&Task> {
+        self.get_tasks()
+            .filter(|task| task.is_milestone())
+    }
+
+    fn milestone_reached(&self, task_id: &TaskId) -> Result<bool, Error> {
+        self.get(task_id)?;
+        let dependencies = self.leaf_dependencies(task_id);
+        Ok(!dependencies.is_empty() && dependencies
+            .iter()
+            .all(|(id, _)| self.get(id).map(|dependency| dependency.status == TaskStatus::Done).unwrap_or(false)))
+    }
+
+    /// Recompute the status of `task_id` if it is a milestone, along with the status of its
+    /// ancestors
+    fn refresh_milestone(&mut self, task_id: &TaskId) -> Result<(), Error> {
+        if !self.get(task_id)?.is_milestone() {
+            return Ok(())
+        }
+        let status = if self.milestone_reached(task_id)? { TaskStatus::Done } else { TaskStatus::InProgress };
+        self.get_mut(task_id)?.status = status;
+        task_id
+            .parent()?
+            .path()
+            .rev()
+            .try_for_each(|id| {
+                let status = if self.children_are_done(&id) { TaskStatus::Done } else { TaskStatus::InProgress };
+                self.get_mut(&id)?.status = status;
+                Ok(())
+            })
+    }
+
     pub fn next_sibling(&self, task_id: &TaskId) -> Result<&Task, Error> {
         let next_sibling_id = task_id.next_sibling()?;
         self.get(&next_sibling_id)
@@ -136,9 +302,8 @@ impl Tasks {
         // get parent
         let parent_task = self.get_mut(&parent_task_id)?;
 
-        // trunks can't be or have dependencies, so we need to check
-        if !parent_task.dependencies.is_empty() || !parent_task.dependency_for.is_empty() {
-            return Err(Error::CannotConvertToTrunk(parent_task_id))
+        if parent_task.is_milestone() {
+            return Err(Error::MilestoneCannotHaveChildren(parent_task_id))
         }
 
         // increase number of children
@@ -147,8 +312,11 @@ impl Tasks {
         // get new task id
         let task_id = parent_task_id.new_child_id(parent_task.num_child)?;
 
-        // create task
-        let task = Task::new(task_id.clone(), name);
+        // create task, which takes over the work of the parent if it was a leaf until now
+        let mut task = Task::new(task_id.clone(), name);
+        if parent_task.num_child == 1 {
+            Self::hand_down(parent_task, &mut task);
+        }
 
         // add task to task map
         self.insert(task_id.clone(), task);
@@ -157,10 +325,36 @@ impl Tasks {
         self.apply_along_path(&task_id, |task| {
             task.status = TaskStatus::InProgress;
         })?;
+        self.refresh_due_dates(&parent_task_id)?;
+
+        // milestones waiting on the parent now wait on the new task too
+        self.leaf_dependents(&task_id)
+            .into_iter()
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>()
+            .iter()
+            .try_for_each(|id| self.refresh_milestone(id))?;
 
         self.get_mut(&task_id)
     }
 
+    /// Move what only leaves carry from `parent` to `child`, its first child, so the numbers
+    /// rolled up to the parent stay the same
+    fn hand_down(parent: &mut Task, child: &mut Task) {
+        child.planned_value = parent.planned_value;
+        child.actual_cost = parent.actual_cost;
+        child.due_date = parent.due_date;
+        child.estimate = parent.estimate.take();
+        child.expected_effort = parent.expected_effort;
+        child.effort_variance = parent.effort_variance;
+        child.priority = std::mem::take(&mut parent.priority);
+        child.planned_dates = parent.planned_dates.take();
+        child.required_skills = std::mem::take(&mut parent.required_skills);
+        child.work_logs = std::mem::take(&mut parent.work_logs);
+        child.manual_planned_value = parent.manual_planned_value.replace(false);
+        child.manual_actual_cost = parent.manual_actual_cost.replace(false);
+    }
+
     pub(crate) fn expand<const N: usize>(&mut self, arr: &[(&str, &str); N]) -> Result<&mut Self, Error> {
         for (parent_id, task_name) in arr {
             self.add_task(TaskId::parse(parent_id)?, task_name)?;
@@ -168,6 +362,112 @@ impl Tasks {
         Ok(self)
     }
 
+    fn split_amounts(task_id: &TaskId, total: f64, amounts: &SplitAmounts, num_child: usize) -> Result<Vec<f64>, Error> {
+        match amounts {
+            SplitAmounts::Even => {
+                // last child takes the rounding remainder, so the sum stays exactly the same
+                let share = total / num_child as f64;
+                let mut values = vec![share; num_child];
+                values[num_child - 1] = total - share * (num_child - 1) as f64;
+                Ok(values)
+            },
+            SplitAmounts::Explicit(values) => {
+                if values.len() != num_child
+                    || values.iter().any(|value| *value < 0.0)
+                    || (values.iter().sum::<f64>() - total).abs() > EPSILON {
+                    return Err(Error::SplitAmountsMismatch(task_id.clone()))
+                }
+                Ok(values.clone())
+            }
+        }
+    }
+
+    /// Turns the leaf `task_id` into a trunk with one child per name. Its planned value and
+    /// actual cost are spread across the new children, so the trunk keeps the same numbers.
+    /// Dependency edges are moved to the child at index `target` (starting from 1), whose id
+    /// is returned.
+    pub(crate) fn split_task(&mut self, task_id: &TaskId, names: &[&str], planned_values: &SplitAmounts, actual_costs: &SplitAmounts, target: u32) -> Result<TaskId, Error> {
+        let task = self.get(task_id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotBeSplit(task_id.clone()))
+        }
+        if task.is_milestone() {
+            return Err(Error::MilestoneCannotHaveChildren(task_id.clone()))
+        }
+        if names.is_empty() {
+            return Err(Error::SplitWithoutSubtasks(task_id.clone()))
+        }
+        if target == 0 || target as usize > names.len() {
+            return Err(Error::BadSplitTarget(task_id.clone(), target))
+        }
+        let planned_values = Self::split_amounts(task_id, task.planned_value, planned_values, names.len())?;
+        let actual_costs = Self::split_amounts(task_id, task.actual_cost, actual_costs, names.len())?;
+        let status = task.status.clone();
+        let target_id = task_id.new_child_id(target)?;
+
+        // the trunk keeps its own values, since they are the sum of the new children
+        let (dependencies, dependency_for, dependency_links) = {
+            let task = self.get_mut(task_id)?;
+            task.num_child = names.len() as u32;
+            (std::mem::take(&mut task.dependencies), std::mem::take(&mut task.dependency_for), std::mem::take(&mut task.dependency_links))
+        };
+
+        for (idx, name) in names.iter().enumerate() {
+            let child_id = task_id.new_child_id(idx as u32 + 1)?;
+            let mut child = Task::new(child_id.clone(), name);
+            child.planned_value = planned_values[idx];
+            child.actual_cost = actual_costs[idx];
+            child.status = status.clone();
+            self.insert(child_id, child);
+        }
+
+        // move dependency edges from the old leaf to the target child
+        for dependency_id in &dependencies {
+            let dependency = self.get_mut(dependency_id)?;
+            dependency.dependency_for.remove(task_id);
+            dependency.dependency_for.insert(target_id.clone());
+        }
+        for dependent_id in &dependency_for {
+            let dependent = self.get_mut(dependent_id)?;
+            dependent.dependencies.remove(task_id);
+            dependent.dependencies.insert(target_id.clone());
+            if let Some(link) = dependent.dependency_links.remove(task_id) {
+                dependent.dependency_links.insert(target_id.clone(), link);
+            }
+        }
+        let target_task = self.get_mut(&target_id)?;
+        target_task.dependencies = dependencies;
+        target_task.dependency_for = dependency_for;
+        target_task.dependency_links = dependency_links;
+
+        // every subtask keeps the due date and the required skills of the old leaf
+        let task = self.get_mut(task_id)?;
+        let (due_date, required_skills) = (task.due_date, std::mem::take(&mut task.required_skills));
+        task.planned_dates = None;
+        for child_id in task_id.child_ids(names.len() as u32) {
+            let child = self.get_mut(&child_id)?;
+            child.due_date = due_date;
+            child.required_skills = required_skills.clone();
+        }
+
+        // the whole estimate goes to the target child, so the rolled up variance stays the same
+        let task = self.get_mut(task_id)?;
+        let (estimate, expected_effort, effort_variance) = (task.estimate.take(), task.expected_effort, task.effort_variance);
+        let target_task = self.get_mut(&target_id)?;
+        target_task.estimate = estimate;
+        target_task.expected_effort = expected_effort;
+        target_task.effort_variance = effort_variance;
+
+        // and so do the logged hours and the priority
+        let task = self.get_mut(task_id)?;
+        let (work_logs, priority) = (std::mem::take(&mut task.work_logs), std::mem::take(&mut task.priority));
+        let target_task = self.get_mut(&target_id)?;
+        target_task.work_logs = work_logs;
+        target_task.priority = priority;
+
+        Ok(target_id)
+    }
+
     fn apply_along_path<F: Fn(&mut Task)>(&mut self, id: &TaskId, func: F) -> Result<(), Error> {
         id
             .path()
@@ -200,8 +500,8 @@ impl Tasks {
         if self.get(task_id)?.num_child > 0 {
             return Err(Error::TrunkCannotBeRemoved(task_id.clone()));
         }
-        // task can't be removed if there are members assigned to it
-        if members.members().any(|member| member.is_assigned_to(task_id)) {
+        // task can't be removed if there are members or a team assigned to it
+        if members.is_assigned(task_id) || members.assigned_team(task_id).is_some() {
             return Err(Error::CannotRemoveAssignedTask(task_id.clone()))
         }
         // task can't be removed if has or is a dependency
@@ -212,8 +512,8 @@ impl Tasks {
         self.remove_task_stats_from_tree(task_id)?;
 
         let parent_id = task_id.parent()?;
-        let parent_childs: _ = {
-            let mut parent = self.get_mut(&parent_id)?;
+        let parent_childs = {
+            let parent = self.get_mut(&parent_id)?;
             parent.num_child -= 1;
             parent.id()
                 .child_ids(parent.num_child+1)
@@ -223,48 +523,133 @@ impl Tasks {
         let layer_idx = task_id.len() - 1;
         let child_idx = task_id.child_idx()? as usize - 1;
 
-        let task = self.remove_task(&task_id)?;
+        let task = self.remove_task(task_id)?;
 
         // change id of child that comes after id node
         parent_childs.iter().enumerate().try_for_each(|(index, child_id)| -> Result<(), _> {
             if child_idx < index {
-                self.subtract_id(&child_id, layer_idx)?;
+                self.subtract_id(child_id, layer_idx)?;
             }
             Ok(())
         })?;
+        self.refresh_due_dates(&parent_id)?;
 
         Ok(task)
     }
 
     fn remove_task_stats_from_tree(&mut self, task_id: &TaskId) -> Result<(), Error> {
+        // milestones have no effort to remove
+        if self.get(task_id)?.is_milestone() {
+            return Ok(())
+        }
 
-        self.set_actual_cost(&task_id, 0.0)?;
-        self.set_planned_value(&task_id, 0.0)?;
+        self.set_actual_cost(task_id, 0.0)?;
+        self.set_planned_value(task_id, 0.0)?;
+        self.set_estimate(task_id, None)
+    }
+
+    pub(crate) fn set_estimate(&mut self, task_id: &TaskId, estimate: Option<Estimate>) -> Result<(), Error> {
+        let parent_id = task_id.parent()?;
+        let task = self.get_mut(task_id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotChangeEstimate(task_id.clone()));
+        }
+        if task.is_milestone() {
+            return Err(Error::MilestoneCannotHaveEffort(task_id.clone()));
+        }
+        let expected_effort = estimate.map_or(0.0, |estimate| estimate.expected());
+        let effort_variance = estimate.map_or(0.0, |estimate| estimate.variance());
+        let expected_diff = expected_effort - task.expected_effort;
+        let variance_diff = effort_variance - task.effort_variance;
+        task.estimate = estimate;
+        task.expected_effort = expected_effort;
+        task.effort_variance = effort_variance;
+
+        self.apply_along_path(&parent_id, |task| {
+            task.expected_effort += expected_diff;
+            task.effort_variance += variance_diff;
+        })
+    }
+
+    pub(crate) fn set_priority(&mut self, task_id: &TaskId, priority: i32) -> Result<(), Error> {
+        let task = self.get_mut(task_id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotHavePriority(task_id.clone()));
+        }
+        task.priority = priority;
+        Ok(())
+    }
+
+    pub(crate) fn require_skill(&mut self, task_id: &TaskId, skill: &str, level: Proficiency) -> Result<(), Error> {
+        let task = self.get_mut(task_id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotRequireSkill(task_id.clone()));
+        }
+        task.required_skills.insert(skill.to_string(), level);
         Ok(())
     }
 
+    pub(crate) fn remove_required_skill(&mut self, task_id: &TaskId, skill: &str) -> Result<(), Error> {
+        self.get_mut(task_id)?
+            .required_skills
+            .remove(skill)
+            .ok_or_else(|| Error::SkillNotRequired(task_id.clone(), skill.to_string()))?;
+        Ok(())
+    }
+
+    pub(crate) fn set_planned_dates(&mut self, task_id: &TaskId, planned_dates: Option<(NaiveDate, NaiveDate)>) -> Result<(), Error> {
+        self.get_mut(task_id)?.planned_dates = planned_dates;
+        Ok(())
+    }
+
+    /// Planned dates of a leaf, or the span of the planned leaves under a trunk
+    pub fn planned_dates(&self, task_id: &TaskId) -> Option<(NaiveDate, NaiveDate)> {
+        self.leaves_under(task_id)
+            .into_iter()
+            .filter_map(|id| self.get(id).ok().and_then(|task| task.planned_dates()))
+            .reduce(|(start, finish), (leaf_start, leaf_finish)| (start.min(leaf_start), finish.max(leaf_finish)))
+    }
+
+    pub(crate) fn set_due_date(&mut self, task_id: &TaskId, due_date: Option<NaiveDate>) -> Result<(), Error> {
+        let task = self.get_mut(task_id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotChangeDueDate(task_id.clone()));
+        }
+        task.due_date = due_date;
+        self.refresh_due_dates(&task_id.parent()?)
+    }
+
+    /// Recompute the due date of `task_id` and its ancestors from their children, if they are
+    /// trunks
+    fn refresh_due_dates(&mut self, task_id: &TaskId) -> Result<(), Error> {
+        task_id
+            .path()
+            .rev()
+            .try_for_each(|id| {
+                let task = self.get(&id)?;
+                if task.is_leaf() {
+                    return Ok(())
+                }
+                let due_date = task.child_ids()
+                    .filter_map(|child_id| self.get(&child_id).ok()?.due_date)
+                    .max();
+                self.get_mut(&id)?.due_date = due_date;
+                Ok(())
+            })
+    }
+
     fn children_are_done(&self, task_id: &TaskId) -> bool {
         self.get(task_id).unwrap()
             .child_ids()
-            .find(|id| self.get(id).unwrap().status != TaskStatus::Done)
-            .is_none()
+            .filter_map(|id| self.store.get(&id))
+            .all(|child| child.status == TaskStatus::Done)
     }
 
     pub(crate) fn set_actual_cost(&mut self, task_id: &TaskId, actual_cost: f64) -> Result<(), Error> {
-        let parent_id = task_id.parent()?;
-        {
-            let mut task = self.get_mut(&task_id)?;
-            if task.is_trunk() {
-                return Err(Error::TrunkCannotChangeCost(task_id.clone()));
-            }
-            let old_actual_cost = task.actual_cost;
-            task.actual_cost = actual_cost;
-            let diff = actual_cost - old_actual_cost;
-
-                self.apply_along_path(&parent_id, |mut task| {
-                    task.actual_cost += diff;
-                })?;
+        if self.get(task_id)?.is_milestone() {
+            return Err(Error::MilestoneCannotHaveEffort(task_id.clone()));
         }
+        self.update_actual_cost(task_id, actual_cost)?;
 
         task_id
             .clone()
@@ -275,26 +660,221 @@ impl Tasks {
                     self.get_mut(&id)?.status = TaskStatus::Done;
                 }
                 Ok(())
-            })
+            })?;
+
+        // milestones waiting on this task may have been reached
+        self.leaf_dependents(task_id)
+            .into_iter()
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>()
+            .iter()
+            .try_for_each(|id| self.refresh_milestone(id))
+    }
+
+    /// Change the actual cost of a leaf, rolling it up, without marking the leaf as done
+    pub(crate) fn update_actual_cost(&mut self, task_id: &TaskId, actual_cost: f64) -> Result<(), Error> {
+        let parent_id = task_id.parent()?;
+        let task = self.get_mut(task_id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotChangeCost(task_id.clone()));
+        }
+        let old_actual_cost = task.actual_cost;
+        task.actual_cost = actual_cost;
+        let diff = actual_cost - old_actual_cost;
+
+        self.apply_along_path(&parent_id, |task| {
+            task.actual_cost += diff;
+        })
+    }
+
+    pub(crate) fn add_work_log(&mut self, task_id: &TaskId, log: WorkLog) -> Result<(), Error> {
+        let task = self.get_mut(task_id)?;
+        if task.is_trunk() {
+            return Err(Error::TrunkCannotChangeCost(task_id.clone()));
+        }
+        if task.is_milestone() {
+            return Err(Error::MilestoneCannotHaveEffort(task_id.clone()));
+        }
+        if !(log.hours > 0.0 && log.hours <= 24.0) {
+            return Err(Error::BadWorkLog(log.hours.to_string()));
+        }
+        task.work_logs.push(log);
+        Ok(())
+    }
+
+    /// Credit to `new` the hours logged by `old`
+    pub(crate) fn rename_member(&mut self, old: &str, new: &str) {
+        self.store
+            .values_mut()
+            .flat_map(|task| task.work_logs.iter_mut())
+            .filter(|log| log.member == old)
+            .for_each(|log| log.member = new.to_string());
     }
 
     pub(crate) fn set_planned_value(&mut self, task_id: &TaskId, planned_value: f64) -> Result<(), Error> {
         let parent_id = task_id.parent()?;
-        let mut task = self.get_mut(&task_id)?;
+        let task = self.get_mut(task_id)?;
         // can't set actual cost of trunk node
         if task.is_trunk() {
             return Err(Error::TrunkCannotChangeValue(task_id.clone()));
         }
+        if task.is_milestone() {
+            return Err(Error::MilestoneCannotHaveEffort(task_id.clone()));
+        }
         let old_planned_value = task.planned_value;
         task.planned_value = planned_value;
         let diff = planned_value - old_planned_value;
 
-        self.apply_along_path(&parent_id, |mut task| {
+        self.apply_along_path(&parent_id, |task| {
             task.planned_value += diff;
         })
     }
 
+    pub(crate) fn sorted_ids(&self) -> Vec<&TaskId> {
+        let mut ids = self.store.keys().collect::<Vec<_>>();
+        ids.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+        ids
+    }
+
+    pub(crate) fn validate(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for id in self.sorted_ids() {
+            let task = self.get(id).unwrap();
+
+            // hierarchy
+            if let Ok(parent_id) = id.parent() {
+                match self.store.get(&parent_id) {
+                    Some(parent) if id.child_idx().unwrap() <= parent.num_child => {},
+                    _ => violations.push(Violation::OrphanTask(id.clone()))
+                }
+            }
+            let children = task.child_ids()
+                .filter_map(|child_id| match self.store.get(&child_id) {
+                    Some(child) => Some(child),
+                    None => {
+                        violations.push(Violation::MissingChild { id: id.clone(), child: child_id });
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            // rollup
+            if task.is_trunk() {
+                let planned_value = children.iter().map(|child| child.planned_value).sum::<f64>();
+                if (task.planned_value - planned_value).abs() > EPSILON {
+                    violations.push(Violation::PlannedValueMismatch { id: id.clone(), stored: task.planned_value, expected: planned_value });
+                }
+                let actual_cost = children.iter().map(|child| child.actual_cost).sum::<f64>();
+                if (task.actual_cost - actual_cost).abs() > EPSILON {
+                    violations.push(Violation::ActualCostMismatch { id: id.clone(), stored: task.actual_cost, expected: actual_cost });
+                }
+                let expected_effort = children.iter().map(|child| child.expected_effort).sum::<f64>();
+                let effort_variance = children.iter().map(|child| child.effort_variance).sum::<f64>();
+                if (task.expected_effort - expected_effort).abs() > EPSILON || (task.effort_variance - effort_variance).abs() > EPSILON {
+                    violations.push(Violation::EffortMismatch { id: id.clone(), stored: task.expected_effort, expected: expected_effort });
+                }
+                let status = if self.children_are_done(id) { TaskStatus::Done } else { TaskStatus::InProgress };
+                if task.status != status {
+                    violations.push(Violation::StatusMismatch { id: id.clone(), stored: task.status.clone(), expected: status });
+                }
+            } else if task.is_milestone() {
+                let status = if self.milestone_reached(id).unwrap() { TaskStatus::Done } else { TaskStatus::InProgress };
+                if task.status != status {
+                    violations.push(Violation::StatusMismatch { id: id.clone(), stored: task.status.clone(), expected: status });
+                }
+            }
+
+            // dependency edges must be mirrored
+            for dependency_id in &task.dependencies {
+                match self.store.get(dependency_id) {
+                    None => violations.push(Violation::DanglingDependency { id: id.clone(), dependency: dependency_id.clone() }),
+                    Some(dependency) if !dependency.dependency_for.contains(id) =>
+                        violations.push(Violation::UnmirroredDependency { id: id.clone(), dependency: dependency_id.clone() }),
+                    _ => {}
+                }
+            }
+            for dependent_id in &task.dependency_for {
+                match self.store.get(dependent_id) {
+                    None => violations.push(Violation::DanglingDependent { id: id.clone(), dependent: dependent_id.clone() }),
+                    Some(dependent) if !dependent.dependencies.contains(id) =>
+                        violations.push(Violation::UnmirroredDependent { id: id.clone(), dependent: dependent_id.clone() }),
+                    _ => {}
+                }
+            }
+        }
+        violations
+    }
+
+    /// Recompute everything that can be derived from the leaves and the `dependencies` sets:
+    /// trunk values and status, and `dependency_for`. References to missing tasks are dropped.
+    pub(crate) fn repair(&mut self) {
+        // `dependencies` is the source of truth of the dependency graph, since it is the one
+        // checked for cycles
+        let ids = self.store.keys().cloned().collect::<HashSet<_>>();
+        let mut edges = Vec::new();
+        self.store.values_mut().for_each(|task| {
+            task.dependencies.retain(|dependency_id| ids.contains(dependency_id));
+            let dependencies = &task.dependencies;
+            task.dependency_links.retain(|dependency_id, _| dependencies.contains(dependency_id));
+            task.dependency_for.clear();
+            edges.extend(task.dependencies.iter().map(|dependency_id| (task.id.clone(), dependency_id.clone())));
+        });
+        for (task_id, dependency_id) in edges {
+            self.get_mut(&dependency_id).unwrap().dependency_for.insert(task_id);
+        }
+
+        let milestone_ids = self.get_milestones().map(|task| task.id.clone()).collect::<Vec<_>>();
+        for id in milestone_ids {
+            let status = if self.milestone_reached(&id).unwrap() { TaskStatus::Done } else { TaskStatus::InProgress };
+            self.get_mut(&id).unwrap().status = status;
+        }
+
+        // deepest trunks first, so each one sums already repaired children
+        let mut trunk_ids = self.store.values()
+            .filter(|task| task.is_trunk())
+            .map(|task| task.id.clone())
+            .collect::<Vec<_>>();
+        trunk_ids.sort_by_key(|id| std::cmp::Reverse(id.len()));
+        for id in trunk_ids {
+            let children = self.get(&id).unwrap()
+                .child_ids()
+                .filter_map(|child_id| self.store.get(&child_id))
+                .collect::<Vec<_>>();
+            let planned_value = children.iter().map(|child| child.planned_value).sum();
+            let actual_cost = children.iter().map(|child| child.actual_cost).sum();
+            let expected_effort = children.iter().map(|child| child.expected_effort).sum();
+            let effort_variance = children.iter().map(|child| child.effort_variance).sum();
+            let status = if self.children_are_done(&id) { TaskStatus::Done } else { TaskStatus::InProgress };
+            let task = self.get_mut(&id).unwrap();
+            task.planned_value = planned_value;
+            task.actual_cost = actual_cost;
+            task.expected_effort = expected_effort;
+            task.effort_variance = effort_variance;
+            task.status = status;
+        }
+    }
+
     pub fn to_dot_str(&self) -> String {
+        self.to_dot_str_with(&ViewOptions::default())
+    }
+
+    fn view<'a>(&self, options: &'a ViewOptions) -> View<'a> {
+        let team_colors = options.teams
+            .values()
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .zip(TEAM_COLORS.iter().copied().cycle())
+            .collect();
+        View {
+            options,
+            critical_path: options.critical_path.then(|| self.critical_path()),
+            team_colors,
+        }
+    }
+
+    pub fn to_dot_str_with(&self, options: &ViewOptions) -> String {
+        let view = self.view(options);
         let stats = format!(
             "earned value: {}, spi: {}, sv: {}, cpi: {}, cv: {}",
             self.earned_value(),
@@ -302,35 +882,114 @@ impl Tasks {
             self.sv(),
             self.cpi(),
             self.cv());
+        let legend = if view.team_colors.is_empty() {
+            String::new()
+        } else {
+            view.team_colors
+                .iter()
+                .fold("\tsubgraph cluster_teams {\n\t\tlabel=\"Teams\"\n".to_string(), |s, (team, color)| {
+                    s + &format!("\t\t\"team {}\" [label=\"{}\", shape=box, style=filled, fillcolor={}]\n", team, team, color)
+                }) + "\t}\n"
+        };
         format!(
-            "digraph G {{\ngraph [label=\"{}\", rankdir=TB, splines=true, layout=dot, overlap=true, newrank=true];\n{}}}",
+            "digraph G {{\ngraph [label=\"{}\", rankdir=TB, splines=true, layout=dot, overlap=true, newrank=true];\n{}{}}}",
             stats,
-            self.subtasks_to_dot_str(&TaskId::get_root_id()))
+            self.subtasks_to_dot_str(&TaskId::get_root_id(), &view),
+            legend)
+    }
+
+    fn estimate_str(task: &Task) -> String {
+        match task.estimate() {
+            Some(estimate) if estimate.is_three_point() => format!("{} ({:.1}h ±{:.1})", estimate, estimate.expected(), estimate.std_dev()),
+            Some(estimate) => estimate.to_string(),
+            None if task.is_trunk() => format!("{:.1}h ±{:.1}", task.expected_effort(), task.effort_std_dev()),
+            None => "none".to_string()
+        }
+    }
+
+    /// Leaves that take effort but have no estimate yet
+    fn lacks_estimate(task: &Task) -> bool {
+        task.is_leaf() && !task.is_milestone() && task.estimate().is_none()
     }
 
-    fn subtasks_to_dot_str(&self, root_id: &TaskId) -> String {
+    fn task_to_dot_str(task: &Task, view: &View) -> String {
+        let mut s = task.to_dot_str();
+        if view.options.estimates && !task.is_milestone() {
+            s += &format!("\nestimate: {}", Self::estimate_str(task));
+        }
+        if let Some((start, finish)) = task.planned_dates().filter(|_| view.options.schedule && task.is_leaf()) {
+            s += &format!("\nplanned: {} → {}", start, finish);
+        }
+        s
+    }
+
+    fn subtasks_to_dot_str(&self, root_id: &TaskId, view: &View) -> String {
         let mut s = String::new();
         let root = self.get(root_id).unwrap();
-        let root_str = root.to_dot_str();
+        let root_str = Self::task_to_dot_str(root, view);
+
+        if root.is_milestone() {
+            s += &format!("\t\"{}\" [shape=diamond]\n", root_str);
+        }
+        if let Some(color) = view.options.teams.get(root_id).and_then(|team| view.team_colors.get(team.as_str())) {
+            s += &format!("\t\"{}\" [style=filled, fillcolor={}]\n", root_str, color);
+        }
+        if view.options.estimates && Self::lacks_estimate(root) {
+            s += &format!("\t\"{}\" [color=orange]\n", root_str);
+        }
+        let critical_path = view.critical_path.as_ref();
+        if critical_path.is_some_and(|path| path.is_critical(root_id)) {
+            s += &format!("\t\"{}\" [color=red, penwidth=2]\n", root_str);
+        }
 
         root.child_ids().for_each(|child_id| {
             let child = self.get(&child_id).unwrap();
-            s += &format!("\t\"{}\" -> \"{}\" [style=solid, weight=100, constraint=true]\n", root_str, child.to_dot_str());
+            s += &format!("\t\"{}\" -> \"{}\" [style=solid, weight=100, constraint=true]\n", root_str, Self::task_to_dot_str(child, view));
         });
         root.child_ids().for_each(|child_id| {
-            s += &self.subtasks_to_dot_str(&child_id);
+            s += &self.subtasks_to_dot_str(&child_id, view);
         });
 
         // dependencies
         root.dependencies.iter()
-            .map(|child_id| self.get(&child_id).unwrap())
+            .map(|child_id| self.get(child_id).unwrap())
             .for_each(|child| {
-                s += &format!("\t\"{}\" -> \"{}\" [style=dashed, weight=0, constraint=false]\n", root_str, child.to_dot_str());
+                let highlight = if critical_path.is_some_and(|path| path.is_critical_edge(root_id, child.id())) {
+                    ", color=red, penwidth=2"
+                } else {
+                    ""
+                };
+                s += &format!(
+                    "\t\"{}\" -> \"{}\" [style=dashed, weight=0, constraint=false, label=\"{}\"{}]\n",
+                    root_str,
+                    Self::task_to_dot_str(child, view),
+                    root.dependency_link(child.id()),
+                    highlight);
             });
         s
     }
 
-    fn subtasks_to_tree_str(&self, root_id: &TaskId, prefix: &str) -> String {
+    fn task_to_tree_str(&self, task: &Task, view: &View) -> String {
+        let mut s = task.to_string();
+        if view.options.estimates && Self::lacks_estimate(task) {
+            s += " [no estimate]";
+        } else if view.options.estimates && !task.is_milestone() {
+            s += &format!(" [estimate: {}]", Self::estimate_str(task));
+        }
+        if let Some(times) = view.critical_path.as_ref().and_then(|path| path.times(task.id())) {
+            if times.is_critical() {
+                s += " [critical]";
+            } else {
+                s += &format!(" [slack: {:.1}d]", times.total_slack());
+            }
+        }
+        if let Some((start, finish)) = self.planned_dates(task.id()).filter(|_| view.options.schedule) {
+            s += &format!(" [planned: {} → {}]", start, finish);
+        }
+        s
+    }
+
+    fn subtasks_to_tree_str(&self, root_id: &TaskId, prefix: &str, view: &View) -> String {
         let mut s = String::new();
         let root = self.get(root_id).unwrap();
 
@@ -339,12 +998,12 @@ impl Tasks {
 
             match self.next_sibling(&child_id) {
                 Ok(_) => {
-                    s += &format!("{}├─ {}\n", prefix, child);
-                    s += &self.subtasks_to_tree_str(&child_id, &format!("{}│  ", prefix));
+                    s += &format!("{}├─ {}\n", prefix, self.task_to_tree_str(child, view));
+                    s += &self.subtasks_to_tree_str(&child_id, &format!("{}│  ", prefix), view);
                 },
                 Err(_) => {
-                    s += &format!("{}└─ {}\n", prefix, child);
-                    s += &self.subtasks_to_tree_str(&child_id, &format!("{}   ", prefix));
+                    s += &format!("{}└─ {}\n", prefix, self.task_to_tree_str(child, view));
+                    s += &self.subtasks_to_tree_str(&child_id, &format!("{}   ", prefix), view);
                 }
             }
         });
@@ -352,12 +1011,50 @@ impl Tasks {
     }
 
     pub fn to_tree_str(&self) -> String {
+        self.to_tree_str_with(&ViewOptions::default())
+    }
+
+    pub fn to_tree_str_with(&self, options: &ViewOptions) -> String {
+        let view = self.view(options);
         let root_id = &TaskId::get_root_id();
         let root = self.get(root_id).unwrap();
         format!(
             "{}\n{}",
-            root,
-            self.subtasks_to_tree_str(&TaskId::get_root_id(), ""))
+            self.task_to_tree_str(root, &view),
+            self.subtasks_to_tree_str(&TaskId::get_root_id(), "", &view))
+    }
+
+    /// Mermaid Gantt chart of the leaves, with a section per top-level task. Milestones are
+    /// diamonds on their target date, other leaves are bars over the dates planned by the last
+    /// resource leveling. Leaves that were never planned are left out.
+    pub fn to_gantt_str(&self) -> String {
+        let mut leaves = self.get_tasks()
+            .filter(|task| task.is_milestone() || task.planned_dates().is_some())
+            .collect::<Vec<_>>();
+        leaves.sort_by(|a, b| a.id().as_vec().cmp(b.id().as_vec()));
+        let mut s = format!("gantt\n    title {}\n    dateFormat YYYY-MM-DD\n", Self::gantt_label(self.name()));
+        let mut section = None;
+        for task in leaves {
+            let top = task.id().path().nth(1).unwrap();
+            if section.as_ref() != Some(&top) {
+                s += &format!("    section {}\n", Self::gantt_label(self.get(&top).unwrap().name()));
+                section = Some(top);
+            }
+            let label = Self::gantt_label(&format!("{} {}", task.id(), task.name()));
+            let done = if task.status == TaskStatus::Done { "done, " } else { "" };
+            let id = format!("t{}", task.id().as_vec().iter().map(|i| i.to_string()).collect::<Vec<_>>().join("_"));
+            match (task.target_date(), task.planned_dates()) {
+                (Some(target_date), _) => s += &format!("    {} :milestone, {}{}, {}, 0d\n", label, done, id, target_date),
+                (None, Some((start, finish))) => s += &format!("    {} :{}{}, {}, {}d\n", label, done, id, start, (finish - start).num_days() + 1),
+                (None, None) => {}
+            }
+        }
+        s
+    }
+
+    /// Mermaid ends names at `:` and `;`, and comments start at `#`
+    fn gantt_label(name: &str) -> String {
+        name.replace([':', ';', '#'], " ")
     }
 
     pub fn get(&self, task_id: &TaskId) -> Result<&Task, Error> {
@@ -378,6 +1075,10 @@ impl Tasks {
         self.store.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
     pub fn get_tasks(&self) -> impl Iterator<Item=&Task> {
         self.store
             .values()
@@ -399,49 +1100,70 @@ impl Tasks {
             .filter(|task| task.status == TaskStatus::Done)
     }
 
-    pub fn get_dependency_roots(&self) -> impl Iterator<Item=&Task> {
-        self.get_tasks()
-            .filter(|task| task.dependency_for.is_empty())
+    /// Unfinished leaves whose due date is before `today`
+    pub fn get_late_tasks<'a>(&'a self, today: &'a NaiveDate) -> impl Iterator<Item=&'a Task> {
+        self.get_todo_tasks()
+            .filter(move |task| task.due_date().is_some_and(|due_date| due_date < today))
     }
 
-    fn dfs_has_cycle<'a>(&'a self, stack: &mut Vec<&'a TaskId>, visited: &mut HashMap<&'a TaskId, DFSVertexStatus> ) -> Result<bool, Error> {
+    /// Unfinished leaves due from `today` up to `days` days later
+    pub fn get_due_soon_tasks<'a>(&'a self, today: &'a NaiveDate, days: i64) -> impl Iterator<Item=&'a Task> {
+        self.get_todo_tasks()
+            .filter(move |task| task.due_date().is_some_and(|due_date| {
+                due_date >= today && (*due_date - *today).num_days() <= days
+            }))
+    }
 
-        let top = stack.last().cloned().unwrap();
-        for neighbour in self.get(top)?.dependencies.iter() {
-            match visited.get(neighbour) {
-                Some(DFSVertexStatus::InStack) => {
-                    return Ok(true);
-                },
-                Some(DFSVertexStatus::Done) => {},
-                Some(DFSVertexStatus::NotVisited) | None => {
-                    stack.push(neighbour);
-                    visited.insert(neighbour, DFSVertexStatus::InStack);
-                    if self.dfs_has_cycle(stack, visited)? {
-                        return Ok(true);
-                    }
-                }
-            }
-        }
-        visited.insert(top, DFSVertexStatus::Done);
-        stack.pop();
-        Ok(false)
+    /// Unfinished leaves whose dependencies are all done, so work on them can start. Milestones
+    /// are left out, since there is no work to do on them.
+    pub fn get_ready_tasks(&self) -> impl Iterator<Item=&Task> {
+        self.get_todo_tasks()
+            .filter(|task| !task.is_milestone())
+            .filter(|task| self.leaf_dependencies(task.id())
+                .iter()
+                .all(|(id, _)| self.get(id).is_ok_and(|dependency| dependency.status == TaskStatus::Done)))
     }
 
-    fn dependency_cycle_exists(&self) -> Result<bool, Error> {
-        let mut visited : HashMap<&TaskId, DFSVertexStatus> = self.get_tasks()
-            .map(|t| (t.id(), DFSVertexStatus::NotVisited))
-            .collect::<HashMap<&TaskId, DFSVertexStatus>>();
+    pub fn get_dependency_roots(&self) -> impl Iterator<Item=&Task> {
+        self.get_tasks()
+            .filter(|task| self.leaf_dependents(task.id()).is_empty())
+    }
 
-        for task_id in self.get_tasks().map(|t| t.id()) {
-            if let Some(DFSVertexStatus::NotVisited) = visited.get(task_id) {
-                let mut stack : Vec<&TaskId> = vec![task_id];
-                visited.insert(task_id, DFSVertexStatus::InStack);
-                if self.dfs_has_cycle(&mut stack, &mut visited)? {
-                    return Ok(true);
+    /// Cycle that `task_id` depending on `dependency_id` would close, if any. Only the leaves
+    /// reachable from `dependency_id` are visited, so the cost doesn't grow with the size of the
+    /// whole graph. The cycle is made of leaves, as the shortest chain `t -> d -> ... -> t`
+    /// where `t` is under `task_id`, `d` is under `dependency_id` and each one depends on the
+    /// next.
+    fn dependency_cycle(&self, task_id: &TaskId, dependency_id: &TaskId) -> Option<Vec<TaskId>> {
+        let targets = self.leaves_under(task_id).into_iter().collect::<HashSet<_>>();
+        let mut starts = self.leaves_under(dependency_id);
+        starts.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+
+        // breadth first, remembering where each leaf was reached from
+        let mut previous : HashMap<&TaskId, Option<&TaskId>> = starts.iter().map(|id| (*id, None)).collect();
+        let mut queue = starts.into_iter().collect::<VecDeque<_>>();
+        while let Some(id) = queue.pop_front() {
+            if targets.contains(id) {
+                let mut cycle = vec![id.clone()];
+                let mut current = id;
+                while let Some(Some(next)) = previous.get(current) {
+                    cycle.push((*next).clone());
+                    current = next;
+                }
+                cycle.push(id.clone());
+                cycle.reverse();
+                return Some(cycle)
+            }
+            let mut neighbours = self.leaf_dependencies(id);
+            neighbours.sort_by(|(a, _), (b, _)| a.as_vec().cmp(b.as_vec()));
+            for (neighbour, _) in neighbours {
+                if !previous.contains_key(neighbour) {
+                    previous.insert(neighbour, Some(id));
+                    queue.push_back(neighbour);
                 }
             }
         }
-        Ok(false)
+        None
     }
 }
 
@@ -540,6 +1262,244 @@ mod tests {
         assert_eq!(tasks.get_mut(&task_id_2_1), Ok(&mut Task::new(TaskId::new(vec![2,1]), "Create plot visualizer")));
 
         assert_eq!(tasks.add_dependency(&task_id_1_1, &task_id_2_1), Ok(()));
-        assert_eq!(tasks.add_dependency(&task_id_2_1, &task_id_1_1), Err(Error::EdgeCreationLeadsToCycle(task_id_2_1.clone(), task_id_1_1.clone())));
+        assert_eq!(tasks.add_dependency(&task_id_2_1, &task_id_1_1), Err(Error::EdgeCreationLeadsToCycle(task_id_2_1.clone(), task_id_1_1.clone(), vec![task_id_2_1.clone(), task_id_1_1.clone(), task_id_2_1.clone()])));
+    }
+
+    #[test]
+    fn split_task() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Create CLI tool"),
+            ("", "Write docs"),
+        ]).unwrap();
+        let task_id_1 = TaskId::new(vec![1]);
+        let task_id_2 = TaskId::new(vec![2]);
+        let task_id_1_1 = TaskId::new(vec![1, 1]);
+        let task_id_1_2 = TaskId::new(vec![1, 2]);
+        let task_id_1_3 = TaskId::new(vec![1, 3]);
+        tasks.set_planned_value(&task_id_1, 9.0).unwrap();
+        tasks.add_dependency(&task_id_2, &task_id_1).unwrap();
+        tasks.set_priority(&task_id_1, 3).unwrap();
+
+        let names = ["Parse arguments", "Help menu", "Output"];
+        assert_eq!(tasks.split_task(&task_id_1, &names, &SplitAmounts::Even, &SplitAmounts::Even, 4), Err(Error::BadSplitTarget(task_id_1.clone(), 4)));
+        assert_eq!(tasks.split_task(&task_id_1, &names, &SplitAmounts::Explicit(vec![1.0, 2.0]), &SplitAmounts::Even, 1), Err(Error::SplitAmountsMismatch(task_id_1.clone())));
+        assert_eq!(tasks.split_task(&task_id_1, &names, &SplitAmounts::Explicit(vec![1.0, 2.0, 3.0]), &SplitAmounts::Even, 1), Err(Error::SplitAmountsMismatch(task_id_1.clone())));
+        assert_eq!(tasks.split_task(&task_id_1, &names, &SplitAmounts::Explicit(vec![-1.0, 5.0, 5.0]), &SplitAmounts::Even, 1), Err(Error::SplitAmountsMismatch(task_id_1.clone())));
+        assert!(tasks.get(&task_id_1).unwrap().is_leaf());
+
+        assert_eq!(tasks.split_task(&task_id_1, &names, &SplitAmounts::Explicit(vec![2.0, 3.0, 4.0]), &SplitAmounts::Even, 2), Ok(task_id_1_2.clone()));
+        assert!(tasks.get(&task_id_1).unwrap().is_trunk());
+        assert_eq!(tasks.planned_value(), 9.0);
+        assert_eq!(tasks.get(&task_id_1).unwrap().get_planned_value(), 9.0);
+        assert_eq!(tasks.get(&task_id_1_1).unwrap().get_planned_value(), 2.0);
+        assert_eq!(tasks.get(&task_id_1_3).unwrap().get_planned_value(), 4.0);
+
+        // dependency edges now point at the chosen subtask
+        assert!(tasks.get(&task_id_1).unwrap().dependency_for.is_empty());
+        assert!(tasks.get(&task_id_1_2).unwrap().dependency_for.contains(&task_id_2));
+        assert!(tasks.get(&task_id_2).unwrap().dependencies.contains(&task_id_1_2));
+        assert!(!tasks.get(&task_id_2).unwrap().dependencies.contains(&task_id_1));
+        // so does the priority, which trunks can't have
+        assert_eq!(tasks.get(&task_id_1).unwrap().priority(), 0);
+        assert_eq!(tasks.get(&task_id_1_2).unwrap().priority(), 3);
+        assert_eq!(tasks.get(&task_id_1_1).unwrap().priority(), 0);
+
+        assert_eq!(tasks.split_task(&task_id_1, &names, &SplitAmounts::Even, &SplitAmounts::Even, 1), Err(Error::TrunkCannotBeSplit(task_id_1.clone())));
+
+        // splitting a finished leaf keeps it done and spreads its cost
+        tasks.set_actual_cost(&task_id_1_1, 3.0).unwrap();
+        assert_eq!(tasks.split_task(&task_id_1_1, &["Flags", "Subcommands"], &SplitAmounts::Even, &SplitAmounts::Even, 1), Ok(TaskId::new(vec![1, 1, 1])));
+        assert_eq!(tasks.get(&TaskId::new(vec![1, 1, 2])).unwrap().get_actual_cost(), 1.5);
+        assert_eq!(tasks.get(&TaskId::new(vec![1, 1, 2])).unwrap().status, TaskStatus::Done);
+        assert_eq!(tasks.get(&task_id_1_1).unwrap().get_actual_cost(), 3.0);
+        assert_eq!(tasks.actual_cost(), 3.0);
+    }
+
+    #[test]
+    fn milestones() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Backend"),
+                ("1", "Database"),
+                ("1", "API"),
+        ]).unwrap();
+        let target_date = NaiveDate::from_ymd(2022, 12, 1);
+        let task_id_1_1 = TaskId::new(vec![1, 1]);
+        let task_id_1_2 = TaskId::new(vec![1, 2]);
+        let milestone_id = TaskId::new(vec![2]);
+        assert_eq!(tasks.add_milestone(TaskId::get_root_id(), "Release", target_date).unwrap().id(), &milestone_id);
+        assert_eq!(tasks.get_milestones().count(), 1);
+        assert_eq!(tasks.add_task(milestone_id.clone(), "Subtask").err(), Some(Error::MilestoneCannotHaveChildren(milestone_id.clone())));
+        assert_eq!(tasks.set_planned_value(&milestone_id, 1.0), Err(Error::MilestoneCannotHaveEffort(milestone_id.clone())));
+
+        // milestones without dependencies are never reached
+        assert_eq!(tasks.get(&milestone_id).unwrap().status, TaskStatus::InProgress);
+
+        tasks.add_dependency(&milestone_id, &task_id_1_1).unwrap();
+        tasks.add_dependency(&milestone_id, &task_id_1_2).unwrap();
+        assert!(matches!(tasks.add_dependency(&task_id_1_1, &milestone_id), Err(Error::EdgeCreationLeadsToCycle(..))));
+
+        tasks.set_actual_cost(&task_id_1_1, 1.0).unwrap();
+        assert_eq!(tasks.get(&milestone_id).unwrap().status, TaskStatus::InProgress);
+        tasks.set_actual_cost(&task_id_1_2, 1.0).unwrap();
+        assert_eq!(tasks.get(&milestone_id).unwrap().status, TaskStatus::Done);
+        assert_eq!(tasks.get(&TaskId::get_root_id()).unwrap().status, TaskStatus::Done);
+
+        tasks.add_task(TaskId::get_root_id(), "Docs").unwrap();
+        tasks.add_dependency(&milestone_id, &TaskId::new(vec![3])).unwrap();
+        assert_eq!(tasks.get(&milestone_id).unwrap().status, TaskStatus::InProgress);
+        tasks.remove_dependency(&milestone_id, &TaskId::new(vec![3])).unwrap();
+        assert_eq!(tasks.get(&milestone_id).unwrap().status, TaskStatus::Done);
+        assert!(tasks.validate().is_empty());
+
+        // milestones are diamonds in both DOT and Gantt output
+        assert!(tasks.to_dot_str().contains("target: 2022-12-01\" [shape=diamond]"));
+        tasks.set_planned_dates(&task_id_1_1, Some((NaiveDate::from_ymd(2022, 11, 28), NaiveDate::from_ymd(2022, 11, 30)))).unwrap();
+        let gantt = tasks.to_gantt_str();
+        assert!(gantt.starts_with("gantt\n    title Project\n    dateFormat YYYY-MM-DD\n    section Backend\n"));
+        assert!(gantt.contains("    1.1 Database :done, t1_1, 2022-11-28, 3d\n"));
+        assert!(gantt.contains("    section Release\n    2 Release :milestone, done, t2, 2022-12-01, 0d\n"));
+        assert!(!gantt.contains("API"));
+    }
+
+    #[test]
+    fn dependency_cycle() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Backend"),
+                ("1", "Database"),
+                ("1", "API"),
+            ("", "Frontend"),
+                ("2", "Login"),
+            ("", "Docs"),
+        ]).unwrap();
+        let task_id_1_2 = TaskId::new(vec![1, 2]);
+        let task_id_2_1 = TaskId::new(vec![2, 1]);
+        let task_id_3 = TaskId::new(vec![3]);
+        tasks.add_dependency(&task_id_3, &task_id_2_1).unwrap();
+        tasks.add_dependency(&task_id_2_1, &task_id_1_2).unwrap();
+
+        let error = tasks.add_dependency(&task_id_1_2, &task_id_3).unwrap_err();
+        assert_eq!(error, Error::EdgeCreationLeadsToCycle(
+            task_id_1_2.clone(),
+            task_id_3.clone(),
+            vec![task_id_1_2.clone(), task_id_3.clone(), task_id_2_1.clone(), task_id_1_2.clone()]));
+        assert!(error.to_string().ends_with("1.2 -> 3 -> 2.1 -> 1.2"));
+        // the rejected edge leaves no trace
+        assert!(tasks.get(&task_id_1_2).unwrap().dependencies.is_empty());
+        assert!(tasks.get(&task_id_3).unwrap().dependency_for.is_empty());
+    }
+
+    #[test]
+    fn trunk_dependencies() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Backend"),
+                ("1", "Database"),
+                ("1", "API"),
+            ("", "Frontend"),
+                ("2", "Login"),
+        ]).unwrap();
+        let task_id_1 = TaskId::new(vec![1]);
+        let task_id_1_1 = TaskId::new(vec![1, 1]);
+        let task_id_1_2 = TaskId::new(vec![1, 2]);
+        let task_id_2 = TaskId::new(vec![2]);
+        let task_id_2_1 = TaskId::new(vec![2, 1]);
+        tasks.add_dependency(&task_id_2, &task_id_1).unwrap();
+        assert_eq!(tasks.add_dependency(&task_id_1_1, &task_id_2_1), Err(Error::EdgeCreationLeadsToCycle(task_id_1_1.clone(), task_id_2_1.clone(), vec![task_id_1_1.clone(), task_id_2_1.clone(), task_id_1_1.clone()])));
+        assert_eq!(tasks.add_dependency(&task_id_1, &task_id_1_2), Err(Error::EdgeCreationLeadsToCycle(task_id_1.clone(), task_id_1_2.clone(), vec![task_id_1_2.clone(), task_id_1_2.clone()])));
+
+        let mut dependencies = tasks.leaf_dependencies(&task_id_2_1).into_iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+        dependencies.sort_by(|a, b| a.as_vec().cmp(b.as_vec()));
+        assert_eq!(dependencies, vec![task_id_1_1.clone(), task_id_1_2.clone()]);
+        assert_eq!(tasks.get_ready_tasks().count(), 2);
+
+        // the dependency holds for leaves added later, on both sides
+        let task_id_1_3 = tasks.add_task(task_id_1.clone(), "Cache").unwrap().id().clone();
+        let task_id_2_2 = tasks.add_task(task_id_2.clone(), "Settings").unwrap().id().clone();
+        assert_eq!(tasks.leaf_dependencies(&task_id_2_2).len(), 3);
+        assert_eq!(tasks.leaf_dependents(&task_id_1_3).len(), 2);
+        let order = tasks.topological_order();
+        let position = |id: &TaskId| order.iter().position(|other| *other == id).unwrap();
+        assert!(position(&task_id_1_3) < position(&task_id_2_1));
+
+        // a leaf can also become a trunk without losing its dependencies
+        let task_id_3 = tasks.add_milestone(TaskId::get_root_id(), "Release", NaiveDate::from_ymd(2022, 12, 1)).unwrap().id().clone();
+        tasks.add_dependency(&task_id_3, &task_id_2).unwrap();
+        tasks.add_task(task_id_2_1.clone(), "Form").unwrap();
+        assert!(tasks.get(&task_id_2).unwrap().dependencies.contains(&task_id_1));
+        assert!(tasks.validate().is_empty());
+    }
+
+    #[test]
+    fn due_dates() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Backend"),
+                ("1", "Database"),
+                ("1", "API"),
+        ]).unwrap();
+        let task_id_1 = TaskId::new(vec![1]);
+        let task_id_1_1 = TaskId::new(vec![1, 1]);
+        let task_id_1_2 = TaskId::new(vec![1, 2]);
+        let early = NaiveDate::from_ymd(2022, 10, 1);
+        let late = NaiveDate::from_ymd(2022, 11, 1);
+
+        assert_eq!(tasks.set_due_date(&task_id_1, Some(early)), Err(Error::TrunkCannotChangeDueDate(task_id_1.clone())));
+        tasks.set_due_date(&task_id_1_1, Some(late)).unwrap();
+        tasks.set_due_date(&task_id_1_2, Some(early)).unwrap();
+        assert_eq!(tasks.get(&task_id_1).unwrap().due_date(), Some(&late));
+        assert_eq!(tasks.get(&TaskId::get_root_id()).unwrap().due_date(), Some(&late));
+        tasks.set_due_date(&task_id_1_1, None).unwrap();
+        assert_eq!(tasks.get(&task_id_1).unwrap().due_date(), Some(&early));
+
+        let today = NaiveDate::from_ymd(2022, 10, 5);
+        assert_eq!(tasks.get_late_tasks(&today).map(|t| t.id()).collect::<Vec<_>>(), vec![&task_id_1_2]);
+        assert_eq!(tasks.get_due_soon_tasks(&today, 7).count(), 0);
+        tasks.set_due_date(&task_id_1_1, Some(NaiveDate::from_ymd(2022, 10, 9))).unwrap();
+        assert_eq!(tasks.get_due_soon_tasks(&today, 7).map(|t| t.id()).collect::<Vec<_>>(), vec![&task_id_1_1]);
+        tasks.set_actual_cost(&task_id_1_2, 1.0).unwrap();
+        assert_eq!(tasks.get_late_tasks(&today).count(), 0);
+
+        // a leaf getting its first child hands its due date down instead of losing it
+        let due = NaiveDate::from_ymd(2022, 10, 9);
+        let sketch = tasks.add_task(task_id_1_1.clone(), "Sketch").unwrap().id().clone();
+        assert_eq!(tasks.get(&sketch).unwrap().due_date(), Some(&due));
+        assert_eq!(tasks.get(&task_id_1_1).unwrap().due_date(), Some(&due));
+        tasks.add_task(task_id_1_1.clone(), "Review").unwrap();
+        assert_eq!(tasks.get(&TaskId::new(vec![1, 1, 2])).unwrap().due_date(), None);
+        assert_eq!(tasks.get(&task_id_1).unwrap().due_date(), Some(&due));
+    }
+
+    #[test]
+    fn estimates() {
+        let mut tasks = Tasks::new("Project");
+        tasks.expand(&[
+            ("", "Backend"),
+                ("1", "Database"),
+                ("1", "API"),
+        ]).unwrap();
+        let task_id_1 = TaskId::new(vec![1]);
+        let task_id_1_1 = TaskId::new(vec![1, 1]);
+        let task_id_1_2 = TaskId::new(vec![1, 2]);
+        assert_eq!(tasks.set_estimate(&task_id_1, Some(Estimate::new(1.0).unwrap())), Err(Error::TrunkCannotChangeEstimate(task_id_1.clone())));
+
+        tasks.set_estimate(&task_id_1_1, Some(Estimate::three_point(2.0, 4.0, 12.0).unwrap())).unwrap();
+        tasks.set_estimate(&task_id_1_2, Some(Estimate::three_point(1.0, 4.0, 7.0).unwrap())).unwrap();
+        assert_eq!(tasks.expected_effort(), 9.0);
+        assert_eq!(tasks.get(&task_id_1).unwrap().expected_effort(), 9.0);
+        assert!((tasks.get(&task_id_1).unwrap().effort_std_dev() - (136.0_f64 / 36.0).sqrt()).abs() < EPSILON);
+        let options = ViewOptions { estimates: true, ..Default::default() };
+        assert!(tasks.to_tree_str_with(&options).contains("[estimate: 2/4/12h (5.0h ±1.7)]"));
+
+        tasks.set_estimate(&task_id_1_2, None).unwrap();
+        assert_eq!(tasks.expected_effort(), 5.0);
+        assert!(tasks.to_tree_str_with(&options).contains("API ✗ -> [] [no estimate]"));
+
+        tasks.split_task(&task_id_1_1, &["Schema", "Migrations"], &SplitAmounts::Even, &SplitAmounts::Even, 2).unwrap();
+        assert_eq!(tasks.get(&TaskId::new(vec![1, 1, 2])).unwrap().expected_effort(), 5.0);
+        assert_eq!(tasks.get(&task_id_1_1).unwrap().estimate(), None);
+        assert!(tasks.validate().is_empty());
     }
 }
@@ -0,0 +1,19 @@
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+/// Hours a member spent on a leaf on a day
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkLog {
+    pub member: String,
+    pub date: NaiveDate,
+    pub hours: f64,
+}
+
+impl Display for WorkLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {} - {}h", self.date, self.member, self.hours)
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use crate::prelude::{Project, Task, TaskId, TaskStatus, Error};
+
+/// Earned value figures of the leaves a team owns
+#[derive(Clone, Debug, PartialEq)]
+pub struct TeamMetrics {
+    pub team: String,
+    /// Leaves assigned to the team or to any of its members, sorted by id
+    pub tasks: Vec<TaskId>,
+    pub planned_value: f64,
+    pub actual_cost: f64,
+    /// Planned value of the leaves that are done
+    pub earned_value: f64,
+}
+
+impl TeamMetrics {
+    pub fn spi(&self) -> f64 {
+        let res = self.earned_value / self.planned_value;
+        if res.is_nan() { 0.0 } else { res }
+    }
+
+    pub fn sv(&self) -> f64 {
+        self.earned_value - self.planned_value
+    }
+
+    pub fn cpi(&self) -> f64 {
+        let res = self.earned_value / self.actual_cost;
+        if res.is_nan() { 0.0 } else { res }
+    }
+
+    pub fn cv(&self) -> f64 {
+        self.earned_value - self.actual_cost
+    }
+}
+
+impl Project {
+
+    /// Leaves assigned to team `name` or to any of its members, sorted by id
+    pub fn team_tasks(&self, name: &str) -> Result<Vec<&Task>, Error> {
+        let team = self.members.team(name)?;
+        let mut tasks = self.tasks
+            .get_tasks()
+            .filter(|task| self.members.assigned_team(task.id()) == Some(name)
+                || self.members.assignees(task.id()).any(|member| team.has_member(member.name())))
+            .collect::<Vec<_>>();
+        tasks.sort_by(|a, b| a.id().as_vec().cmp(b.id().as_vec()));
+        Ok(tasks)
+    }
+
+    /// Planned value, actual cost and earned value of the leaves team `name` owns. A leaf shared
+    /// with another team counts in full for both.
+    pub fn team_metrics(&self, name: &str) -> Result<TeamMetrics, Error> {
+        let tasks = self.team_tasks(name)?;
+        let sum = |value: &dyn Fn(&Task) -> f64| tasks.iter().fold(0.0, |total, task| total + value(task));
+        Ok(TeamMetrics {
+            team: name.to_string(),
+            tasks: tasks.iter().map(|task| task.id().clone()).collect(),
+            planned_value: sum(&|task| task.get_planned_value()),
+            actual_cost: sum(&|task| task.get_actual_cost()),
+            earned_value: sum(&|task| if task.status == TaskStatus::Done { task.get_planned_value() } else { 0.0 }),
+        })
+    }
+
+    /// Owning team of every leaf that has one, as given by `Members::owning_team`
+    pub fn task_teams(&self) -> HashMap<TaskId, String> {
+        self.tasks
+            .get_tasks()
+            .filter_map(|task| self.members.owning_team(task.id()).map(|team| (task.id().clone(), team.to_string())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::ViewOptions;
+
+    #[test]
+    fn team_metrics() {
+        let mut project = Project::new("Project");
+        let (api, ui, docs) = (TaskId::parse("1").unwrap(), TaskId::parse("2").unwrap(), TaskId::parse("3").unwrap());
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "API"),
+                ("", "UI"),
+                ("", "Docs"),
+            ])?;
+            tasks.planned_value(&api, 1000.0)?;
+            tasks.planned_value(&ui, 600.0)?;
+            tasks.planned_value(&docs, 200.0)?;
+            tasks.done(&api, 1250.0)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.add_team("backend")?;
+            members.add_team("frontend")?;
+            members.join_team("backend", "alice")?;
+            members.join_team("frontend", "bob")?;
+            members.assign_task_to_member(api.clone(), "alice")?;
+            members.assign_task_to_member(ui.clone(), "bob")?;
+            members.assign_team(docs.clone(), "backend")?;
+            Ok(())
+        }).unwrap();
+
+        let backend = project.team_metrics("backend").unwrap();
+        assert_eq!(backend.tasks, vec![api.clone(), docs.clone()]);
+        assert_eq!((backend.planned_value, backend.actual_cost, backend.earned_value), (1200.0, 1250.0, 1000.0));
+        assert_eq!(backend.cpi(), 0.8);
+        assert_eq!(project.team_metrics("frontend").unwrap().spi(), 0.0);
+        assert_eq!(project.team_metrics("qa"), Err(Error::TeamNotFound("qa".to_string())));
+
+        let teams = project.task_teams();
+        assert_eq!(teams.get(&ui).map(String::as_str), Some("frontend"));
+        assert_eq!(teams.get(&docs).map(String::as_str), Some("backend"));
+        let dot = project.tasks().to_dot_str_with(&ViewOptions { teams, ..ViewOptions::default() });
+        assert!(dot.contains("\"team backend\" [label=\"backend\", shape=box, style=filled, fillcolor=lightblue]"));
+        assert!(dot.contains(" ac: 0\" [style=filled, fillcolor=palegreen]"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::Display;
+
+use crate::{prelude::{Project, TaskId}, task::TaskStatus};
+
+/// Inconsistency found in a loaded project
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// Trunk planned value is not the sum of its children's
+    PlannedValueMismatch { id: TaskId, stored: f64, expected: f64 },
+    /// Trunk actual cost is not the sum of its children's
+    ActualCostMismatch { id: TaskId, stored: f64, expected: f64 },
+    /// Trunk expected effort or its variance is not the sum of its children's
+    EffortMismatch { id: TaskId, stored: f64, expected: f64 },
+    /// Trunk or milestone is marked as done while some children or dependencies aren't, or the
+    /// other way around
+    StatusMismatch { id: TaskId, stored: TaskStatus, expected: TaskStatus },
+    /// Task whose parent doesn't exist or doesn't count it as a child
+    OrphanTask(TaskId),
+    /// `num_child` of a trunk points to a child that isn't stored
+    MissingChild { id: TaskId, child: TaskId },
+    /// Task depends on a task that doesn't exist
+    DanglingDependency { id: TaskId, dependency: TaskId },
+    /// Task is listed as a dependency for a task that doesn't exist
+    DanglingDependent { id: TaskId, dependent: TaskId },
+    /// Task depends on another one, but isn't in its `dependency_for`
+    UnmirroredDependency { id: TaskId, dependency: TaskId },
+    /// Task is a dependency for another one, but isn't in its `dependencies`
+    UnmirroredDependent { id: TaskId, dependent: TaskId },
+    /// Member is assigned to a task that doesn't exist
+    UnknownTaskAssignment { member: String, id: TaskId },
+    /// Member is assigned directly to a trunk task
+    TrunkAssignment { member: String, id: TaskId },
+    /// Sprint backlog references a task that doesn't exist
+    UnknownSprintTask { sprint: usize, id: TaskId },
+    /// Hours on a task were logged by a member that doesn't exist, so they can't be priced
+    UnknownWorkLogMember { id: TaskId, member: String },
+    /// Allocations of the assignees of a task add up to more than 100%
+    OverAllocatedTask { id: TaskId, total: u32 },
+    /// Team lists a member that doesn't exist
+    UnknownTeamMember { team: String, member: String },
+    /// Task that doesn't exist is assigned to a team, or a task to a team that doesn't exist
+    UnknownTeamTask { team: String, id: TaskId },
+}
+
+impl Violation {
+    /// Whether `Project::repair` can fix this violation without losing information
+    pub fn is_fixable(&self) -> bool {
+        !matches!(
+            self,
+            Violation::OrphanTask(_) | Violation::MissingChild { .. } | Violation::TrunkAssignment { .. }
+                | Violation::UnknownWorkLogMember { .. } | Violation::OverAllocatedTask { .. }
+        )
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::PlannedValueMismatch { id, stored, expected } =>
+                write!(f, "planned value of '{}' is {}, but its children add up to {}", id, stored, expected),
+            Violation::ActualCostMismatch { id, stored, expected } =>
+                write!(f, "actual cost of '{}' is {}, but its children add up to {}", id, stored, expected),
+            Violation::EffortMismatch { id, stored, expected } =>
+                write!(f, "expected effort of '{}' is {}, but its children add up to {}", id, stored, expected),
+            Violation::StatusMismatch { id, stored, expected } =>
+                write!(f, "status of '{}' is {}, but it should be {}", id, stored, expected),
+            Violation::OrphanTask(id) =>
+                write!(f, "'{}' is not a child of any stored task", id),
+            Violation::MissingChild { id, child } =>
+                write!(f, "'{}' should have child '{}', but it doesn't exist", id, child),
+            Violation::DanglingDependency { id, dependency } =>
+                write!(f, "'{}' depends on '{}', which doesn't exist", id, dependency),
+            Violation::DanglingDependent { id, dependent } =>
+                write!(f, "'{}' is a dependency for '{}', which doesn't exist", id, dependent),
+            Violation::UnmirroredDependency { id, dependency } =>
+                write!(f, "'{}' depends on '{}', but '{}' doesn't list it as a dependent", id, dependency, dependency),
+            Violation::UnmirroredDependent { id, dependent } =>
+                write!(f, "'{}' is a dependency for '{}', but '{}' doesn't list it as a dependency", id, dependent, dependent),
+            Violation::UnknownTaskAssignment { member, id } =>
+                write!(f, "member '{}' is assigned to '{}', which doesn't exist", member, id),
+            Violation::TrunkAssignment { member, id } =>
+                write!(f, "member '{}' is assigned to trunk '{}'", member, id),
+            Violation::UnknownSprintTask { sprint, id } =>
+                write!(f, "backlog of sprint {} has '{}', which doesn't exist", sprint, id),
+            Violation::UnknownWorkLogMember { id, member } =>
+                write!(f, "hours on '{}' were logged by member '{}', who doesn't exist", id, member),
+            Violation::OverAllocatedTask { id, total } =>
+                write!(f, "allocations on '{}' add up to {}%, over 100%", id, total),
+            Violation::UnknownTeamMember { team, member } =>
+                write!(f, "team '{}' has member '{}', who doesn't exist", team, member),
+            Violation::UnknownTeamTask { team, id } =>
+                write!(f, "'{}' is assigned to team '{}', but one of them doesn't exist", id, team),
+        }
+    }
+}
+
+impl Project {
+
+    /// Check that the project is coherent, returning every violation found
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations = self.tasks.validate();
+        violations.extend(self.members.validate(&self.tasks));
+        violations.extend(self.sprints.validate(&self.tasks));
+        violations
+    }
+
+    /// Fix every violation that can be fixed safely, returning the ones that are left
+    pub fn repair(&mut self) -> Vec<Violation> {
+        self.tasks.repair();
+        self.members.repair(&self.tasks);
+        self.sprints.repair(&self.tasks);
+        self.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::prelude::{Estimate, Proficiency};
+
+    #[test]
+    fn validate_and_repair() {
+        let mut project = Project::new("Project");
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Backend"),
+                    ("1", "Database"),
+                    ("1", "API"),
+                ("", "Frontend"),
+            ])?;
+            tasks.planned_value(&TaskId::parse("1.1")?, 3.0)?;
+            tasks.add_dependency(&TaskId::parse("2")?, &TaskId::parse("1.2")?)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.assign_task_to_member(TaskId::parse("1.1")?, "alice")?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(project.validate(), vec![]);
+
+        let task_id_1 = TaskId::parse("1").unwrap();
+        let task_id_1_2 = TaskId::parse("1.2").unwrap();
+        let task_id_2 = TaskId::parse("2").unwrap();
+        project.tasks.get_mut(&task_id_1).unwrap().planned_value = 1.0;
+        project.tasks.get_mut(&task_id_1_2).unwrap().dependency_for.clear();
+        project.members.get_mut("alice").unwrap().add_task(TaskId::parse("7").unwrap(), 100);
+        project.members.get_mut("alice").unwrap().add_task(task_id_1.clone(), 100);
+
+        let violations = project.validate();
+        assert!(violations.contains(&Violation::PlannedValueMismatch { id: task_id_1.clone(), stored: 1.0, expected: 3.0 }));
+        assert!(violations.contains(&Violation::PlannedValueMismatch { id: TaskId::get_root_id(), stored: 3.0, expected: 1.0 }));
+        assert!(violations.contains(&Violation::UnmirroredDependency { id: task_id_2.clone(), dependency: task_id_1_2.clone() }));
+        assert!(violations.contains(&Violation::UnknownTaskAssignment { member: "alice".to_string(), id: TaskId::parse("7").unwrap() }));
+        assert!(violations.contains(&Violation::TrunkAssignment { member: "alice".to_string(), id: task_id_1.clone() }));
+        assert_eq!(violations.len(), 5);
+
+        assert_eq!(project.repair(), vec![Violation::TrunkAssignment { member: "alice".to_string(), id: task_id_1.clone() }]);
+        assert_eq!(project.tasks().get(&task_id_1).unwrap().get_planned_value(), 3.0);
+        assert!(project.tasks().get(&task_id_1_2).unwrap().dependency_for.contains(&task_id_2));
+    }
+
+    #[test]
+    fn leaf_getting_a_child_stays_coherent() {
+        let mut project = Project::new("Project");
+        let (design, sketch) = (TaskId::parse("1").unwrap(), TaskId::parse("1.1").unwrap());
+        project.tasks_mut(|tasks| {
+            tasks.add(TaskId::get_root_id(), "Design")?;
+            tasks.estimate(&design, Some(Estimate::new(16.0)?))?;
+            tasks.planned_value(&design, 800.0)?;
+            tasks.require_skill(&design, "ux", Proficiency::Advanced)?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.set_rate("alice", NaiveDate::from_ymd(2024, 1, 1), 50.0)?;
+            members.assign_task_to_member(design.clone(), "alice")?;
+            Ok(())
+        }).unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.log_work(&design, "alice", NaiveDate::from_ymd(2024, 1, 2), 4.0)?;
+            tasks.add(design.clone(), "Sketch")?;
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(project.validate(), vec![]);
+        let child = project.tasks().get(&sketch).unwrap();
+        assert_eq!(child.expected_effort(), 16.0);
+        assert_eq!((child.get_planned_value(), child.get_actual_cost()), (800.0, 200.0));
+        assert!(child.has_manual_planned_value());
+        assert_eq!(child.required_skills().len(), 1);
+        assert_eq!(child.logged_hours(), 4.0);
+        assert!(project.members().get("alice").unwrap().is_assigned_to(&sketch));
+        let trunk = project.tasks().get(&design).unwrap();
+        assert_eq!((trunk.estimate(), trunk.work_logs().len(), trunk.has_manual_planned_value()), (None, 0, false));
+        assert!(trunk.required_skills().is_empty());
+    }
+}
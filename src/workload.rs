@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Duration, Datelike};
+
+use crate::prelude::{Project, Member, Task, TaskStatus, Error};
+
+/// Hours each member is asked to work and can work, over consecutive periods
+#[derive(Clone, Debug, PartialEq)]
+pub struct Workload {
+    /// First and last day of each period
+    pub periods: Vec<(NaiveDate, NaiveDate)>,
+    /// Sorted by name
+    pub members: Vec<MemberLoad>,
+}
+
+/// Load of a member in each period of a `Workload`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberLoad {
+    pub name: String,
+    /// Hours of work falling in each period
+    pub demand: Vec<f64>,
+    /// Hours available in each period
+    pub capacity: Vec<f64>,
+    /// Hours of unfinished leaves with neither planned dates nor a due date, which can't be
+    /// placed in any period
+    pub unscheduled: f64,
+}
+
+impl MemberLoad {
+    /// Demand over capacity in period `index`, infinite if there is work but no time for it
+    pub fn load(&self, index: usize) -> f64 {
+        match (self.demand[index], self.capacity[index]) {
+            (demand, _) if demand <= 0.0 => 0.0,
+            (_, capacity) if capacity <= 0.0 => f64::INFINITY,
+            (demand, capacity) => demand / capacity,
+        }
+    }
+
+    pub fn is_over_allocated(&self, index: usize) -> bool {
+        self.load(index) > 1.0 + 1e-9
+    }
+}
+
+impl Workload {
+    /// Members over 100% in some period, with the indices of those periods
+    pub fn over_allocated(&self) -> Vec<(&str, Vec<usize>)> {
+        self.members
+            .iter()
+            .map(|member| (member.name.as_str(), (0..self.periods.len()).filter(|i| member.is_over_allocated(*i)).collect::<Vec<_>>()))
+            .filter(|(_, periods)| !periods.is_empty())
+            .collect()
+    }
+
+    /// One line per member and period, with a header
+    pub fn to_csv(&self) -> String {
+        let mut s = "member,start,end,demand,capacity,load\n".to_string();
+        for member in &self.members {
+            for (i, (start, end)) in self.periods.iter().enumerate() {
+                s += &format!("{},{},{},{:.2},{:.2},{:.4}\n", member.name, start, end, member.demand[i], member.capacity[i], member.load(i));
+            }
+        }
+        s
+    }
+}
+
+impl Project {
+
+    /// Load of every member in each week, from the Monday of the week of `from` to the week of
+    /// `to`
+    pub fn weekly_workload(&self, from: NaiveDate, to: NaiveDate) -> Result<Workload, Error> {
+        if from > to {
+            return Err(Error::BadDateRange(from, to))
+        }
+        let mut start = from - Duration::days(from.weekday().num_days_from_monday() as i64);
+        let mut periods = vec![];
+        while start <= to {
+            periods.push((start, start + Duration::days(6)));
+            start += Duration::weeks(1);
+        }
+        Ok(self.workload(periods))
+    }
+
+    /// Load of every member in each sprint
+    pub fn sprint_workload(&self) -> Workload {
+        let periods = self.sprints
+            .sprints_date_times()
+            .map(|(start, end)| (start.naive_utc(), end.naive_utc() - Duration::days(1)))
+            .collect();
+        self.workload(periods)
+    }
+
+    /// Each unfinished leaf asks its assignees for their share of its remaining effort, or for
+    /// its planned value at their hourly rate if it has no estimate. The hours are spread over
+    /// the planned dates of the leaf, or from the first period to its due date if it isn't
+    /// planned, following the availability of the member on each day. Overdue leaves land on
+    /// the first day.
+    fn workload(&self, periods: Vec<(NaiveDate, NaiveDate)>) -> Workload {
+        let mut members = self.members.members().collect::<Vec<_>>();
+        members.sort_by_key(|member| member.name());
+        let first_day = periods.first().map(|(start, _)| *start);
+        let members = members
+            .into_iter()
+            .map(|member| {
+                let mut daily : HashMap<NaiveDate, f64> = HashMap::new();
+                let mut unscheduled = 0.0;
+                for task in member.task_ids().filter_map(|id| self.tasks.get(id).ok()) {
+                    if task.status == TaskStatus::Done {
+                        continue;
+                    }
+                    let hours = self.demanded_hours(task, member);
+                    let window = task.planned_dates()
+                        .or_else(|| first_day.zip(task.due_date().copied()).map(|(start, due)| (start, due.max(start))));
+                    match window {
+                        Some((start, finish)) => self.spread(member, start, finish, hours, &mut daily),
+                        None => unscheduled += hours,
+                    }
+                }
+                let demand = periods
+                    .iter()
+                    .map(|(start, end)| daily.iter().filter(|(date, _)| *date >= start && *date <= end).fold(0.0, |sum, (_, hours)| sum + hours))
+                    .collect();
+                let capacity = periods
+                    .iter()
+                    .map(|(start, end)| self.working_days(*start, *end).map(|date| member.hours_on(&date)).sum())
+                    .collect();
+                MemberLoad { name: member.name().to_string(), demand, capacity, unscheduled }
+            })
+            .collect();
+        Workload { periods, members }
+    }
+
+    /// Share of leaf `task` that falls on `member`
+    fn demanded_hours(&self, task: &Task, member: &Member) -> f64 {
+        let hours = if task.estimate().is_some() {
+            (task.expected_effort() - task.logged_hours()).max(0.0)
+        } else {
+            let date = task.planned_dates().map(|(start, _)| start).or_else(|| task.due_date().copied());
+            date.and_then(|date| member.rate_on(&date))
+                .filter(|rate| *rate > 0.0)
+                .map_or(0.0, |rate| task.get_planned_value() / rate)
+        };
+        hours * self.members.share(task.id(), member.name())
+    }
+
+    /// Add `hours` to the days from `start` to `finish`, in proportion to the hours `member`
+    /// can work on each of them, or evenly if they can't work on any
+    fn spread(&self, member: &Member, start: NaiveDate, finish: NaiveDate, hours: f64, daily: &mut HashMap<NaiveDate, f64>) {
+        let days = self.working_days(start, finish).map(|date| (date, member.hours_on(&date))).collect::<Vec<_>>();
+        let total = days.iter().map(|(_, hours)| hours).sum::<f64>();
+        if total > 0.0 {
+            days.iter().for_each(|(date, available)| *daily.entry(*date).or_default() += hours * available / total);
+        } else {
+            let count = (finish - start).num_days() + 1;
+            (0..count).for_each(|offset| *daily.entry(start + Duration::days(offset)).or_default() += hours / count as f64);
+        }
+    }
+
+    /// Days from `start` to `end` that aren't holidays
+    fn working_days(&self, start: NaiveDate, end: NaiveDate) -> impl Iterator<Item=NaiveDate> + '_ {
+        (0..=(end - start).num_days())
+            .map(move |offset| start + Duration::days(offset))
+            .filter(|date| !self.calendar.is_holiday(date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{TaskId, Estimate};
+
+    #[test]
+    fn weekly_workload() {
+        let mut project = Project::new("Project");
+        project.tasks_mut(|tasks| {
+            tasks.expand(&[
+                ("", "Backend"),
+                ("", "Frontend"),
+                ("", "Docs"),
+            ])?;
+            tasks.estimate(&TaskId::parse("1")?, Some(Estimate::new(60.0)?))?;
+            tasks.estimate(&TaskId::parse("2")?, Some(Estimate::new(16.0)?))?;
+            tasks.estimate(&TaskId::parse("3")?, Some(Estimate::new(4.0)?))?;
+            tasks.due_date(&TaskId::parse("2")?, Some(NaiveDate::from_ymd(2024, 1, 12)))?;
+            Ok(())
+        }).unwrap();
+        project.members_mut(|members| {
+            members.add_member("alice")?;
+            members.add_member("bob")?;
+            members.assign_task_to_member(TaskId::parse("1")?, "alice")?;
+            members.assign_task_to_member(TaskId::parse("2")?, "bob")?;
+            members.assign_task_to_member(TaskId::parse("3")?, "bob")?;
+            members.set_rate("alice", NaiveDate::from_ymd(2023, 1, 1), 50.0)?;
+            Ok(())
+        }).unwrap();
+        project.tasks_mut(|tasks| {
+            tasks.log_work(&TaskId::parse("1")?, "alice", NaiveDate::from_ymd(2023, 12, 29), 12.0)?;
+            Ok(())
+        }).unwrap();
+        // alice is planned to do the 48 hours of backend left in the first week
+        project.tasks.set_planned_dates(&TaskId::parse("1").unwrap(), Some((NaiveDate::from_ymd(2024, 1, 1), NaiveDate::from_ymd(2024, 1, 5)))).unwrap();
+        project.add_holiday(NaiveDate::from_ymd(2024, 1, 1)).unwrap();
+
+        let workload = project.weekly_workload(NaiveDate::from_ymd(2024, 1, 3), NaiveDate::from_ymd(2024, 1, 10)).unwrap();
+        assert_eq!(workload.periods, vec![
+            (NaiveDate::from_ymd(2024, 1, 1), NaiveDate::from_ymd(2024, 1, 7)),
+            (NaiveDate::from_ymd(2024, 1, 8), NaiveDate::from_ymd(2024, 1, 14)),
+        ]);
+        let alice = &workload.members[0];
+        assert_eq!(alice.capacity, vec![32.0, 40.0]);
+        assert_eq!(alice.demand, vec![48.0, 0.0]);
+        assert!(alice.is_over_allocated(0));
+        // the frontend is due on the second Friday, spread over the 9 working days until then
+        let bob = &workload.members[1];
+        assert_eq!(bob.demand.iter().map(|d| (d * 9.0).round()).collect::<Vec<_>>(), vec![64.0, 80.0]);
+        assert_eq!(bob.unscheduled, 4.0);
+        assert_eq!(workload.over_allocated(), vec![("alice", vec![0])]);
+        assert!(workload.to_csv().starts_with("member,start,end,demand,capacity,load\nalice,2024-01-01,2024-01-07,48.00,32.00,1.5000\n"));
+    }
+}